@@ -4,6 +4,7 @@ use heliochrome::{
     camera::Camera,
     color::Color,
     context::{Context, QualitySettings},
+    environment::Environment,
     image::Image,
     load_obj::load_obj,
     materials::*,
@@ -49,10 +50,10 @@ pub fn make_context() -> Context {
         }
         // glass suzanne
         2 => {
-            skybox = SkyBox::Equirectangular(
+            skybox = SkyBox::Equirectangular(Environment::new(
                 Image::load_from_hdri(Path::new("assets/skyboxes/snowy_forest_path_01_4k.hdr"))
                     .unwrap(),
-            );
+            ));
 
             objects.push(Object::new(
                 hittables::InfinitePlane::new(
@@ -76,10 +77,10 @@ pub fn make_context() -> Context {
         }
         // cubes
         3 => {
-            skybox = SkyBox::Equirectangular(
+            skybox = SkyBox::Equirectangular(Environment::new(
                 Image::load_from_hdri(Path::new("assets/skyboxes/snowy_forest_path_01_4k.hdr"))
                     .unwrap(),
-            );
+            ));
 
             let n = 5;
             for x in 0..n {
@@ -129,10 +130,10 @@ pub fn make_context() -> Context {
                 None,
             ));
 
-            skybox = SkyBox::Equirectangular(
+            skybox = SkyBox::Equirectangular(Environment::new(
                 Image::load_from_hdri(Path::new("assets/skyboxes/snowy_forest_path_01_4k.hdr"))
                     .unwrap(),
-            );
+            ));
 
             objects.push(Object::new(
                 hittables::HittableSDF::new(sdf::Torus::new(1.0, 0.5).twist(3.0)),
@@ -190,12 +191,12 @@ pub fn make_context() -> Context {
         }
         // bit of everything
         7 => {
-            skybox = SkyBox::Equirectangular(
+            skybox = SkyBox::Equirectangular(Environment::new(
                 Image::load_from_hdri(Path::new(
                     "assets/skyboxes/christmas_photo_studio_05_4k.hdr",
                 ))
                 .unwrap(),
-            );
+            ));
             camera.eye = vec3::new(3.0, 3.0, -26.0);
 
             objects.push(Object::new(
@@ -262,10 +263,10 @@ pub fn make_context() -> Context {
         }
         // many orbs
         8 => {
-            skybox = SkyBox::Equirectangular(
+            skybox = SkyBox::Equirectangular(Environment::new(
                 Image::load_from_hdri(Path::new("assets/skyboxes/snowy_forest_path_01_4k.hdr"))
                     .unwrap(),
-            );
+            ));
 
             let s = 20i32;
             camera.eye = vec3::splat(s as f32);
@@ -291,7 +292,7 @@ pub fn make_context() -> Context {
         _ => panic!("oof"),
     }
 
-    let scene = Scene::new(camera, skybox, objects);
+    let scene = Scene::new(camera, skybox, objects).unwrap();
 
     Context::new(
         maths::vec2::new(width, height),
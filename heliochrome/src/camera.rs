@@ -1,10 +1,236 @@
-use std::error::Error;
+use std::{error::Error, path::Path};
 
 use crate::{
-    loader::{parse_into, FromHCY},
+    image::Image,
+    loader::{collect_nested_item, parse_into, FromHCY},
     maths::*,
 };
 
+#[derive(Clone, Debug)]
+pub enum ApertureShape {
+    Circle,
+    // a regular polygon made by `blades` straight blades, `rotation` in degrees
+    Polygon { blades: u32, rotation: f32 },
+    // the brightness of an image decides how likely each part of the aperture is
+    Mask(Distribution2D),
+}
+
+impl ApertureShape {
+    // a point on the aperture within the unit square
+    pub fn sample(&self) -> vec2 {
+        match self {
+            ApertureShape::Circle => {
+                let p = vec3::random_in_unit_xy_disk();
+                vec2::new(p.x, p.y)
+            }
+            ApertureShape::Polygon { blades, rotation } => {
+                // every blade makes an equal triangle with the center, so pick one and sample it
+                let blades = (*blades).max(3) as f32;
+                let step = std::f32::consts::TAU / blades;
+                let i = (rand::random::<f32>() * blades).floor();
+                let a0 = rotation.to_radians() + i * step;
+                let (a, b) = (
+                    vec2::new(a0.cos(), a0.sin()),
+                    vec2::new((a0 + step).cos(), (a0 + step).sin()),
+                );
+
+                let r1 = rand::random::<f32>().sqrt();
+                let r2 = rand::random::<f32>();
+                a * (r1 * (1.0 - r2)) + b * (r1 * r2)
+            }
+            ApertureShape::Mask(distribution) => {
+                let (uv, _) = distribution.sample(&vec2::new(rand::random(), rand::random()));
+                uv * 2.0 - vec2::splat(1.0)
+            }
+        }
+    }
+}
+
+impl FromHCY for ApertureShape {
+    fn from_hcy(member: Option<&str>, lines: Vec<String>) -> Result<Self, Box<dyn Error>> {
+        let member = member.ok_or("invalid syntax missing member specifier")?;
+
+        let mut blades = None;
+        let mut rotation = 0.0;
+        let mut path = None;
+        for line in lines.into_iter() {
+            let (key, value) = line
+                .split_once(':')
+                .ok_or("invalid key value pair syntax")?;
+            match key.trim() {
+                "blades" => blades = Some(parse_into(value)?),
+                "rotation" => rotation = parse_into(value)?,
+                "path" => path = Some(value.trim().to_owned()),
+                _ => {}
+            }
+        }
+
+        match member.trim() {
+            "circle" => Ok(ApertureShape::Circle),
+            "polygon" => Ok(ApertureShape::Polygon {
+                blades: blades.ok_or("missing required key `blades`")?,
+                rotation,
+            }),
+            "image" => {
+                let img =
+                    Image::load_texture(Path::new(&path.ok_or("missing required key `path`")?))?;
+                let weights = img.buffer.iter().map(|c| c.luminance()).collect::<Vec<_>>();
+                Ok(ApertureShape::Mask(Distribution2D::new(
+                    &weights,
+                    img.size.x as usize,
+                )))
+            }
+            _ => Err(format!("unknown aperture shape {member}"))?,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum Projection {
+    Perspective,
+    // width of the view in world units, the height follows from the aspect ratio
+    Orthographic { width: f32 },
+    // equidistant, `fov` in degrees is the angle covered by the image circle's diameter
+    Fisheye { fov: f32 },
+    // a full 360 by 180 degree panorama
+    Equirectangular,
+}
+
+impl FromHCY for Projection {
+    fn from_hcy(member: Option<&str>, lines: Vec<String>) -> Result<Self, Box<dyn Error>> {
+        let member = member.ok_or("invalid syntax missing member specifier")?;
+
+        let mut width = None;
+        let mut fov = 180.0;
+        for line in lines.into_iter() {
+            let (key, value) = line
+                .split_once(':')
+                .ok_or("invalid key value pair syntax")?;
+            match key.trim() {
+                "width" => width = Some(parse_into(value)?),
+                "fov" => fov = parse_into(value)?,
+                _ => {}
+            }
+        }
+
+        match member.trim() {
+            "perspective" => Ok(Projection::Perspective),
+            "orthographic" => Ok(Projection::Orthographic {
+                width: width.ok_or("missing required key `width`")?,
+            }),
+            "fisheye" => Ok(Projection::Fisheye { fov }),
+            "equirectangular" => Ok(Projection::Equirectangular),
+            _ => Err(format!("unknown projection {member}"))?,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StereoLayout {
+    // left eye on the left half of the image
+    SideBySide,
+    // left eye on the top half of the image
+    OverUnder,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Stereo {
+    pub layout: StereoLayout,
+    // distance between the eyes in world units
+    pub interocular: f32,
+    // distance of the plane where both eyes line up, defaults to the focus distance
+    pub convergence: Option<f32>,
+}
+
+impl Stereo {
+    pub fn new(layout: StereoLayout, interocular: f32) -> Self {
+        Self {
+            layout,
+            interocular,
+            convergence: None,
+        }
+    }
+
+    // maps the uv of the whole image to the uv within one eye's view, the aspect ratio of that
+    // view and how far the eye is moved to the right
+    fn split(&self, uv: &vec2, aspect: f32) -> (vec2, f32, f32) {
+        let half = self.interocular * 0.5;
+        match self.layout {
+            StereoLayout::SideBySide if uv.x < 0.5 => {
+                (vec2::new(uv.x * 2.0, uv.y), aspect * 0.5, -half)
+            }
+            StereoLayout::SideBySide => (vec2::new(uv.x * 2.0 - 1.0, uv.y), aspect * 0.5, half),
+            StereoLayout::OverUnder if uv.y >= 0.5 => {
+                (vec2::new(uv.x, uv.y * 2.0 - 1.0), aspect * 2.0, -half)
+            }
+            StereoLayout::OverUnder => (vec2::new(uv.x, uv.y * 2.0), aspect * 2.0, half),
+        }
+    }
+}
+
+impl FromHCY for Stereo {
+    fn from_hcy(member: Option<&str>, lines: Vec<String>) -> Result<Self, Box<dyn Error>> {
+        let member = member.ok_or("invalid syntax missing member specifier")?;
+        let layout = match member.trim() {
+            "side by side" => StereoLayout::SideBySide,
+            "over under" => StereoLayout::OverUnder,
+            _ => Err(format!("unknown stereo layout {member}"))?,
+        };
+
+        // roughly the average distance between human eyes, in meters
+        let mut stereo = Stereo::new(layout, 0.064);
+        for line in lines.into_iter() {
+            let (key, value) = line
+                .split_once(':')
+                .ok_or("invalid key value pair syntax")?;
+            match key.trim() {
+                "interocular" => stereo.interocular = parse_into(value)?,
+                "convergence" => stereo.convergence = Some(parse_into(value)?),
+                _ => {}
+            }
+        }
+
+        Ok(stereo)
+    }
+}
+
+// photographic exposure, scaling radiance relative to an exposure value of 0 at iso 100 (f/1
+// for one second), so brighter scenes need the same settings as a real camera would.
+#[derive(Clone, Copy, Debug)]
+pub struct Exposure {
+    pub iso: f32,
+    // in seconds
+    pub shutter: f32,
+    pub f_stop: f32,
+}
+
+impl Exposure {
+    pub fn new(iso: f32, shutter: f32, f_stop: f32) -> Self {
+        Self {
+            iso,
+            shutter,
+            f_stop,
+        }
+    }
+
+    pub fn ev100(&self) -> f32 {
+        (self.f_stop * self.f_stop / self.shutter * 100.0 / self.iso).log2()
+    }
+
+    pub fn scale(&self) -> f32 {
+        (-self.ev100()).exp2()
+    }
+}
+
+// shutter speeds are usually written as fractions of a second
+fn parse_shutter(value: &str) -> Result<f32, Box<dyn Error>> {
+    if let Some((num, den)) = value.split_once('/') {
+        Ok(parse_into::<f32>(num)? / parse_into::<f32>(den)?)
+    } else {
+        parse_into(value)
+    }
+}
+
 #[derive(Debug)]
 pub struct Camera {
     pub eye: vec3,
@@ -14,6 +240,22 @@ pub struct Camera {
     pub size: vec2,
     pub aperture: f32,
     pub focus_dist: Option<f32>,
+    pub projection: Projection,
+    pub aperture_shape: ApertureShape,
+    // how far the lens barrel cuts into the aperture towards the edges of the image, giving
+    // cat's eye shaped bokeh and darker corners. 0 disables it
+    pub vignetting: f32,
+    // overrides the orientation given by `at` and `up`, the camera looks down its -z axis
+    pub rotation: Option<quat>,
+    // in degrees around the view direction
+    pub roll: f32,
+    // in mm, only the height is used to relate focal length and vfov
+    pub sensor_size: vec2,
+    pub exposure: Option<Exposure>,
+    // rays are spread over this interval for motion blur
+    pub shutter_open: f32,
+    pub shutter_close: f32,
+    pub stereo: Option<Stereo>,
 }
 
 impl Camera {
@@ -34,6 +276,16 @@ impl Camera {
             size,
             aperture,
             focus_dist,
+            projection: Projection::Perspective,
+            aperture_shape: ApertureShape::Circle,
+            vignetting: 0.0,
+            rotation: None,
+            roll: 0.0,
+            sensor_size: vec2::new(36.0, 24.0),
+            exposure: None,
+            shutter_open: 0.0,
+            shutter_close: 0.0,
+            stereo: None,
         }
     }
 
@@ -41,31 +293,132 @@ impl Camera {
         (self.eye - self.at).mag()
     }
 
-    pub fn get_ray(&self, uv: &vec2) -> Ray {
-        let h = (self.vfov.to_radians() * 0.5).tan();
-        let viewport_h = 2.0 * h;
-        let viewport_w = self.size.x / self.size.y * viewport_h;
+    pub fn vfov_from_focal_length(focal_length: f32, sensor_height: f32) -> f32 {
+        2.0 * (sensor_height / (2.0 * focal_length)).atan().to_degrees()
+    }
+
+    // in mm, following from the vfov and sensor size
+    pub fn focal_length(&self) -> f32 {
+        self.sensor_size.y / (2.0 * (self.vfov.to_radians() * 0.5).tan())
+    }
+
+    pub fn exposure_scale(&self) -> f32 {
+        self.exposure.map_or(1.0, |exposure| exposure.scale())
+    }
+
+    // right, up and backwards vectors of the camera
+    fn basis(&self) -> (vec3, vec3, vec3) {
+        let (u, v, w) = if let Some(rotation) = self.rotation {
+            (
+                rotation.rotate(vec3::unit_x()),
+                rotation.rotate(vec3::unit_y()),
+                rotation.rotate(vec3::unit_z()),
+            )
+        } else {
+            let w = (self.eye - self.at).normalize();
+            let u = self.up.cross(w).normalize();
+            (u, w.cross(u), w)
+        };
+
+        let (sin, cos) = self.roll.to_radians().sin_cos();
+        (u * cos + v * sin, v * cos - u * sin, w)
+    }
 
-        let w = (self.eye - self.at).normalize();
-        let u = self.up.cross(w).normalize();
-        let v = w.cross(u);
+    // returns `None` for pixels that do not map to a direction, such as outside a fisheye's
+    // image circle
+    pub fn get_ray(&self, uv: &vec2) -> Option<Ray> {
+        let time = lerp(self.shutter_open, self.shutter_close, rand::random());
+        let aspect = self.size.x / self.size.y;
+        let (uv, aspect, eye_offset) = match &self.stereo {
+            Some(stereo) => stereo.split(uv, aspect),
+            None => (*uv, aspect, 0.0),
+        };
+
+        self.project(&uv, aspect, eye_offset).map(|mut ray| {
+            ray.time = time;
+            ray
+        })
+    }
+
+    // `eye_offset` moves the eye along the camera's right vector, or for panoramas
+    // perpendicular to every view direction so both eyes see depth all the way around.
+    fn project(&self, uv: &vec2, aspect: f32, eye_offset: f32) -> Option<Ray> {
+        let (u, v, w) = self.basis();
 
         let focus_dist = self
             .focus_dist
             .unwrap_or_else(|| self.get_default_focus_dist());
+        let convergence = self
+            .stereo
+            .and_then(|stereo| stereo.convergence)
+            .unwrap_or(focus_dist);
+        // turns a direction from an offset eye in so it meets the center one at the convergence
+        // distance
+        let converge =
+            |dir: vec3, offset: vec3| (dir.normalized() * convergence - offset).normalized();
+
+        match self.projection {
+            Projection::Perspective => {}
+            Projection::Orthographic { width } => {
+                let height = width / aspect;
+                let origin =
+                    self.eye + ((uv.x - 0.5) * width + eye_offset) * u + (uv.y - 0.5) * height * v;
+                return Some(Ray::new(origin, -w));
+            }
+            Projection::Fisheye { fov } => {
+                let x = (uv.x * 2.0 - 1.0) * aspect;
+                let y = uv.y * 2.0 - 1.0;
+                let r = (x * x + y * y).sqrt();
+                if r > 1.0 {
+                    return None;
+                }
+
+                let theta = r * (fov * 0.5).to_radians();
+                let phi = y.atan2(x);
+                let dir = theta.sin() * (phi.cos() * u + phi.sin() * v) - theta.cos() * w;
+                let offset = eye_offset * u;
+                return Some(Ray::new(self.eye + offset, converge(dir, offset)));
+            }
+            Projection::Equirectangular => {
+                let phi = (uv.x - 0.5) * std::f32::consts::TAU;
+                let elevation = (uv.y - 0.5) * std::f32::consts::PI;
+                let dir = elevation.cos() * (phi.sin() * u - phi.cos() * w) + elevation.sin() * v;
+                // omni directional stereo, the eyes sit on a circle facing each direction
+                let offset = eye_offset * (phi.cos() * u + phi.sin() * w);
+                return Some(Ray::new(self.eye + offset, converge(dir, offset)));
+            }
+        }
+
+        let h = (self.vfov.to_radians() * 0.5).tan();
+        let viewport_h = 2.0 * h;
+        let viewport_w = aspect * viewport_h;
 
         let horizontal = focus_dist * viewport_w * u;
         let vertical = focus_dist * viewport_h * v;
 
-        let lower_left = self.eye - horizontal * 0.5 - vertical * 0.5 - focus_dist * w;
+        // an off axis frustum so the eyes stay parallel and only the image window shifts
+        let eye = self.eye + eye_offset * u;
+        let lower_left = eye
+            - horizontal * 0.5
+            - vertical * 0.5
+            - focus_dist * w
+            - eye_offset * (focus_dist / convergence) * u;
 
-        let rd = (self.aperture * 0.5) * vec3::random_in_unit_xy_disk();
+        let lens = self.aperture_shape.sample();
+        if self.vignetting > 0.0 {
+            // the barrel is a second circle shifted towards the center of the image
+            let from_center = vec2::new((uv.x * 2.0 - 1.0) * aspect, uv.y * 2.0 - 1.0);
+            if (lens - from_center * self.vignetting).mag() > 1.0 {
+                return None;
+            }
+        }
+        let rd = (self.aperture * 0.5) * lens;
         let offset = u * rd.x + v * rd.y;
 
-        Ray::new(
-            self.eye + offset,
-            (lower_left + uv.x * horizontal + uv.y * vertical - self.eye - offset).normalize(),
-        )
+        Some(Ray::new(
+            eye + offset,
+            (lower_left + uv.x * horizontal + uv.y * vertical - eye - offset).normalize(),
+        ))
     }
 }
 
@@ -78,30 +431,129 @@ impl FromHCY for Camera {
         let mut vfov = None;
         let mut aperture = None;
         let mut focus_dist = None;
+        let mut projection = Projection::Perspective;
+        let mut aperture_shape = ApertureShape::Circle;
+        let mut vignetting = 0.0;
+        let mut rotation = None;
+        let mut roll = 0.0;
+        let mut focal_length = None;
+        let mut sensor_size = None;
+        let mut f_stop = None;
+        let mut iso = None;
+        let mut shutter = None;
+        let mut shutter_open = 0.0;
+        let mut shutter_close = 0.0;
+        let mut stereo = None;
+        let mut units_per_meter = 1.0;
 
-        for line in lines.into_iter() {
+        let mut line_iter = lines.iter();
+        while let Some(line) = line_iter.next() {
             let (key, value) = line
                 .split_once(':')
                 .ok_or("invalid key value pair syntax")?;
             match key.trim() {
-                "eye" => eye = Some(parse_into(value)?),
+                "eye" | "position" => eye = Some(parse_into(value)?),
                 "at" => at = Some(parse_into(value)?),
                 "up" => up = Some(parse_into(value)?),
                 "vfov" => vfov = Some(parse_into(value)?),
                 "aperture" => aperture = Some(parse_into(value)?),
                 "focus_dist" => focus_dist = Some(parse_into(value)?),
+                "aperture shape" => {
+                    aperture_shape = ApertureShape::from_hcy(
+                        Some(value),
+                        collect_nested_item(line, &mut line_iter),
+                    )
+                    .map_err(|err| format!("could not parse aperture shape key: {err}"))?
+                }
+                "vignetting" => vignetting = parse_into(value)?,
+                "rotation" => {
+                    let degrees = parse_into::<vec3>(value)?;
+                    rotation = Some(quat::from_euler(vec3::new(
+                        degrees.x.to_radians(),
+                        degrees.y.to_radians(),
+                        degrees.z.to_radians(),
+                    )));
+                }
+                "quaternion" => rotation = Some(parse_into(value)?),
+                "roll" => roll = parse_into(value)?,
+                "focal length" => focal_length = Some(parse_into(value)?),
+                "sensor size" => sensor_size = Some(parse_into(value)?),
+                "f stop" => f_stop = Some(parse_into::<f32>(value)?),
+                // how big a meter is in the scene, the aperture from `f stop` is scaled by it
+                "units per meter" => units_per_meter = parse_into(value)?,
+                "iso" => iso = Some(parse_into(value)?),
+                "shutter" => shutter = Some(parse_shutter(value)?),
+                "shutter open" => shutter_open = parse_into(value)?,
+                "shutter close" => shutter_close = parse_into(value)?,
+                "stereo" => {
+                    stereo = Some(
+                        Stereo::from_hcy(Some(value), collect_nested_item(line, &mut line_iter))
+                            .map_err(|err| format!("could not parse stereo key: {err}"))?,
+                    )
+                }
+                "projection" => {
+                    projection =
+                        Projection::from_hcy(Some(value), collect_nested_item(line, &mut line_iter))
+                            .map_err(|err| format!("could not parse projection key: {err}"))?
+                }
                 _ => {}
             }
         }
 
-        Ok(Camera::new(
-            eye.ok_or("could not find required key eye.")?,
-            at.ok_or("could not find required key at.")?,
-            up.ok_or("could not find required key up.")?,
-            vfov.ok_or("could not find required key vfov.")?,
+        let eye = eye.ok_or("could not find required key eye.")?;
+        // with a rotation `at` is only needed for the default focus distance, so a lens
+        // without it has to be told where to focus
+        if at.is_none() && focus_dist.is_none() && (aperture.is_some() || f_stop.is_some()) {
+            Err(
+                "could not find required key focus_dist, a camera with an aperture needs it or at.",
+            )?;
+        }
+        let at = match (at, rotation) {
+            (Some(at), _) => at,
+            (None, Some(rotation)) => eye - rotation.rotate(vec3::unit_z()),
+            (None, None) => Err("could not find required key at.")?,
+        };
+        let up = match (up, rotation) {
+            (Some(up), _) => up,
+            (None, Some(_)) => vec3::unit_y(),
+            (None, None) => Err("could not find required key up.")?,
+        };
+        let sensor_size = sensor_size.unwrap_or(vec2::new(36.0, 24.0));
+        let vfov = match (focal_length, vfov) {
+            (Some(focal_length), _) => Camera::vfov_from_focal_length(focal_length, sensor_size.y),
+            (None, Some(vfov)) => vfov,
+            (None, None) => Err("could not find required key vfov or focal length.")?,
+        };
+
+        let mut camera = Camera::new(
+            eye,
+            at,
+            up,
+            vfov,
             vec2::splat(1.0),
-            aperture.ok_or("could not find required key aperture.")?,
+            aperture.unwrap_or(0.0),
             focus_dist,
-        ))
+        );
+        camera.sensor_size = sensor_size;
+        camera.rotation = rotation;
+        camera.roll = roll;
+        camera.shutter_open = shutter_open;
+        camera.shutter_close = shutter_close;
+        camera.stereo = stereo;
+        if let Some(f_stop) = f_stop {
+            // the entrance pupil in mm, converted to scene units
+            camera.aperture = camera.focal_length() / f_stop / 1000.0 * units_per_meter;
+        }
+        if iso.is_some() || shutter.is_some() {
+            camera.exposure = Some(Exposure::new(
+                iso.unwrap_or(100.0),
+                shutter.unwrap_or(1.0),
+                f_stop.unwrap_or(1.0),
+            ));
+        }
+        camera.projection = projection;
+        camera.aperture_shape = aperture_shape;
+        camera.vignetting = vignetting;
+        Ok(camera)
     }
 }
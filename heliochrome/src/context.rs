@@ -15,7 +15,7 @@ use crate::image::Image;
 use crate::loader::SceneConfig;
 use crate::materials::{ScatterType, Scatterable};
 use crate::maths::*;
-use crate::pdf::{ObjectListPdf, ProbabilityDensityFn};
+use crate::pdf::{Pdf, ProbabilityDensityFn};
 use crate::scene::Scene;
 use crate::tonemap::ToneMap;
 
@@ -59,10 +59,33 @@ pub struct Context {
 
 const CHUNK_SIZE: usize = 16;
 
+// contribution of the scene's analytic lights at `p`, these are never hit by rays so they are
+// only picked up here through shadow rays. `eval` gives the scattered color towards a light.
+fn sample_lights(scene: &Scene, p: &vec3, time: f32, eval: impl Fn(&Ray) -> Color) -> Color {
+    let mut color = Color::splat(0.0);
+    for light in scene.lights.iter() {
+        let sample = light.sample(p);
+        let shadow_ray = Ray::new_at_time(*p, sample.dir, time);
+        if scene
+            .intersect(&shadow_ray, 0.001, sample.dist * (1.0 - 0.001))
+            .is_none()
+        {
+            color += eval(&shadow_ray) * sample.color;
+        }
+    }
+    color
+}
+
 pub fn render_fragment(scene: Arc<RwLock<Scene>>, uv: &vec2, bounces: u16) -> Color {
     let scene = scene.read().unwrap();
-    let mut ray = scene.camera.get_ray(uv);
+    let Some(mut ray) = scene.camera.get_ray(uv) else {
+        return Color::splat(0.0);
+    };
+    // materials only know about the hit, so every bounce is put back at the camera ray's time
+    let time = ray.time;
     let mut color = Color::splat(1.0);
+    // light from the analytic lights, weighted by `color` as it was at each bounce
+    let mut direct = Color::splat(0.0);
     for depth in 0..bounces {
         if depth == bounces {
             return Color::splat(0.0);
@@ -71,20 +94,47 @@ pub fn render_fragment(scene: Arc<RwLock<Scene>>, uv: &vec2, bounces: u16) -> Co
         if let Some((intersection, object)) = scene.intersect(&ray, 0.001, f32::INFINITY) {
             let bounce = object.get_bounce_info(&ray, intersection);
 
-            let emitted = object.material.emitted(&bounce);
+            // the partly transparent parts of a stochastic opacity mask
+            if object
+                .material
+                .opacity()
+                .map_or(false, |opacity| opacity.passes_through(&bounce))
+            {
+                ray = Ray::new_at_time(bounce.p, ray.direction, time);
+                continue;
+            }
+
+            let emitted = object.material.emitted(&ray, &bounce);
             if let Some(scatter) = object.material.scatter(&ray, &bounce) {
                 match scatter.scatter_type {
                     ScatterType::Pdf(pdf) => {
-                        let importants = scene.get_importants();
+                        if !scene.lights.is_empty() {
+                            direct += color
+                                * sample_lights(&scene, &bounce.p, time, |shadow_ray| {
+                                    object.material.eval(
+                                        &ray,
+                                        shadow_ray,
+                                        &bounce,
+                                        scatter.attenuation,
+                                    )
+                                });
+                        }
+
+                        let mut importance_pdfs: Vec<Pdf> = vec![];
+                        if let Some(object_pdf) = scene.make_importance_pdf(&bounce.p) {
+                            importance_pdfs.push(object_pdf.into());
+                        }
+                        if let Some(sky_pdf) = scene.skybox.importance_pdf() {
+                            importance_pdfs.push(sky_pdf);
+                        }
 
-                        let (scattered, pdf_val) = if importants.is_empty() {
+                        let (scattered, pdf_val) = if importance_pdfs.is_empty() {
                             let dir = pdf.generate();
                             let scattered = Ray::new(bounce.p, dir);
                             let pdf_val = pdf.value(&scattered.direction);
                             (scattered, pdf_val)
                         } else {
-                            let importance_pdf = ObjectListPdf::new(importants, bounce.p);
-                            let pdf = (importance_pdf, pdf);
+                            let pdf = (importance_pdfs, pdf);
 
                             let dir = pdf.generate();
                             let scattered = Ray::new(bounce.p, dir);
@@ -92,9 +142,11 @@ pub fn render_fragment(scene: Arc<RwLock<Scene>>, uv: &vec2, bounces: u16) -> Co
                             (scattered, pdf_val)
                         };
 
-                        color *= object.material.pdf(&ray, &scattered, &bounce)
-                            * scatter.attenuation
-                            / pdf_val;
+                        color *=
+                            object
+                                .material
+                                .eval(&ray, &scattered, &bounce, scatter.attenuation)
+                                / pdf_val;
                         color += emitted;
                         ray = scattered;
                     }
@@ -102,7 +154,30 @@ pub fn render_fragment(scene: Arc<RwLock<Scene>>, uv: &vec2, bounces: u16) -> Co
                         color *= scatter.attenuation;
                         ray = specular;
                     }
+                    ScatterType::Subsurface(entered) => {
+                        let entered = Ray { time, ..entered };
+                        if let Some((exit, weight, normal)) =
+                            object.material.random_walk(&entered, object)
+                        {
+                            color *= scatter.attenuation * weight;
+                            // the walk leaves the surface like a diffuse reflection would
+                            if !scene.lights.is_empty() {
+                                direct += color
+                                    * sample_lights(&scene, &exit.origin, time, |shadow_ray| {
+                                        Color::splat(
+                                            shadow_ray.direction.dot(normal).max(0.0)
+                                                / std::f32::consts::PI,
+                                        )
+                                    });
+                            }
+                            ray = exit;
+                        } else {
+                            color = Color::splat(0.0);
+                            break;
+                        }
+                    }
                 }
+                ray.time = time;
             } else {
                 color *= emitted;
                 break;
@@ -113,7 +188,7 @@ pub fn render_fragment(scene: Arc<RwLock<Scene>>, uv: &vec2, bounces: u16) -> Co
         }
     }
 
-    color.un_nan()
+    (color + direct).un_nan() * scene.camera.exposure_scale()
 }
 
 impl Context {
@@ -317,3 +392,55 @@ impl Context {
         drop(rs);
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        camera::{Camera, Projection},
+        hittables::InfinitePlane,
+        light::Light,
+        materials::Lambertian,
+        object::Object,
+        scene::SkyBox,
+    };
+
+    #[test]
+    fn point_lights_fall_off_with_the_square_of_distance() {
+        // looking straight down at a grey floor, one unit of image is two units of floor
+        let mut camera = Camera::new(
+            vec3::new(0.0, 1.0, 0.0),
+            vec3::splat(0.0),
+            vec3::new(0.0, 0.0, -1.0),
+            90.0,
+            vec2::new(1.0, 1.0),
+            0.0,
+            None,
+        );
+        camera.projection = Projection::Orthographic { width: 4.0 };
+        let floor = Object::new(
+            InfinitePlane::new(vec3::splat(0.0), vec3::unit_y()),
+            Lambertian::new(Color::splat(0.5)),
+            None,
+        );
+        let mut scene = Scene::new(camera, SkyBox::Color(Color::splat(0.0)), vec![floor]).unwrap();
+        let light = vec3::new(0.0, 2.0, 0.0);
+        scene.lights.push(Light::Point {
+            position: light,
+            color: Color::splat(4.0),
+        });
+        let scene = Arc::new(RwLock::new(scene));
+
+        for (uv, p) in [
+            (vec2::new(0.5, 0.5), vec3::splat(0.0)),
+            (vec2::new(0.75, 0.5), vec3::new(1.0, 0.0, 0.0)),
+            (vec2::new(0.5, 1.0), vec3::new(0.0, 0.0, -2.0)),
+        ] {
+            let to_light = light - p;
+            let cosine = to_light.normalized().y;
+            let expected = 0.5 / std::f32::consts::PI * 4.0 * cosine / to_light.mag_sq();
+            let color = render_fragment(scene.clone(), &uv, 4);
+            assert!((color.r - expected).abs() < 1e-4, "{color:?} != {expected}");
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use std::f32::consts::{PI, TAU};
+
+use crate::{
+    color::Color,
+    image::Image,
+    maths::{vec2, vec3, Distribution2D},
+};
+
+// an equirectangular environment map that can be importance sampled as a light source
+#[derive(Clone, Debug)]
+pub struct Environment {
+    pub image: Image,
+    distribution: Distribution2D,
+    // rotation around the y axis in degrees
+    pub rotation: f32,
+    pub intensity: f32,
+    // in stops, so each step doubles the brightness
+    pub exposure: f32,
+}
+
+impl Environment {
+    pub fn new(image: Image) -> Self {
+        let width = image.size.x as usize;
+        let height = image.size.y as usize;
+
+        // rows near the poles cover less solid angle so they are weighted by sin theta, which
+        // is the cosine of the elevation here.
+        let func = image
+            .buffer
+            .iter()
+            .enumerate()
+            .map(|(i, color)| {
+                let v = ((i / width) as f32 + 0.5) / height as f32;
+                color.luminance().max(0.0) * ((v - 0.5) * PI).cos()
+            })
+            .collect::<Vec<_>>();
+
+        Self {
+            distribution: Distribution2D::new(&func, width),
+            image,
+            rotation: 0.0,
+            intensity: 1.0,
+            exposure: 0.0,
+        }
+    }
+
+    fn rotate(&self, dir: vec3, angle: f32) -> vec3 {
+        let (sin, cos) = angle.to_radians().sin_cos();
+        vec3::new(dir.x * cos - dir.z * sin, dir.y, dir.x * sin + dir.z * cos)
+    }
+
+    fn dir_to_uv(&self, dir: &vec3) -> vec2 {
+        let dir = self.rotate(dir.normalized(), -self.rotation);
+        vec2::new(
+            0.5 + dir.z.atan2(dir.x) / TAU,
+            0.5 + dir.y.clamp(-1.0, 1.0).asin() / PI,
+        )
+    }
+
+    fn uv_to_dir(&self, uv: &vec2) -> vec3 {
+        let phi = (uv.x - 0.5) * TAU;
+        let elevation = (uv.y - 0.5) * PI;
+        let dir = vec3::new(
+            elevation.cos() * phi.cos(),
+            elevation.sin(),
+            elevation.cos() * phi.sin(),
+        );
+        self.rotate(dir, self.rotation)
+    }
+
+    pub fn sample(&self, dir: &vec3) -> Color {
+        self.image.sample_uv(&self.dir_to_uv(dir)) * self.intensity * self.exposure.exp2()
+    }
+
+    // solid angle density of `generate` picking `dir`
+    pub fn pdf_value(&self, dir: &vec3) -> f32 {
+        let uv = self.dir_to_uv(dir);
+        let cos_elevation = ((uv.y - 0.5) * PI).cos();
+        if cos_elevation <= 0.0 {
+            return 0.0;
+        }
+
+        self.distribution.pdf(&uv) / (2.0 * PI * PI * cos_elevation)
+    }
+
+    pub fn generate(&self) -> vec3 {
+        let (uv, _) = self
+            .distribution
+            .sample(&vec2::new(rand::random(), rand::random()));
+        self.uv_to_dir(&uv)
+    }
+}
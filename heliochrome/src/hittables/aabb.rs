@@ -1,9 +1,9 @@
 use std::error::Error;
 
-use super::{BounceInfo, Hittable, Intersection};
+use super::{BounceInfo, Hittable, Intersection, Span};
 use crate::{
     loader::{parse_into, FromHCY},
-    maths::{vec3, Ray},
+    maths::{vec2, vec3, Ray},
 };
 
 #[derive(Debug, Clone, Copy, Default)]
@@ -25,11 +25,22 @@ impl AABB {
         Self::new(a.min.max(&b.min), a.max.min(&b.max))
     }
 
+    // false for the boxes of unbounded shapes, like planes and repeated sdfs
+    pub fn is_finite(&self) -> bool {
+        self.min.min_component().is_finite() && self.max.max_component().is_finite()
+    }
+
     pub fn surface_area(&self) -> f32 {
         let size = self.max - self.min;
         2.0 * (size.x) * (size.y) + (size.x) * (size.z) + (size.y) * (size.z)
     }
 
+    // the area of one face across each axis, the box has two of each
+    fn face_areas(&self) -> [f32; 3] {
+        let size = self.max - self.min;
+        [size.y * size.z, size.x * size.z, size.x * size.y]
+    }
+
     #[inline]
     pub fn hits(&self, ray: &Ray, t_min: f32, t_max: f32) -> bool {
         let inv_d = ray.direction.recip();
@@ -42,6 +53,18 @@ impl AABB {
 
         t_min < t_max
     }
+
+    // the part of [t_min, t_max] the ray spends inside the box
+    pub fn clip(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<(f32, f32)> {
+        let inv_d = ray.direction.recip();
+        let t0 = (self.min - ray.origin) * inv_d;
+        let t1 = (self.max - ray.origin) * inv_d;
+        // f32::max and min skip the nans from rays lying in the plane of an infinite side
+        let t_min = t_min.max(t0.min(&t1).max_component());
+        let t_max = t_max.min(t0.max(&t1).min_component());
+
+        (t_min <= t_max).then_some((t_min, t_max))
+    }
 }
 
 impl Hittable for AABB {
@@ -85,12 +108,127 @@ impl Hittable for AABB {
             vec3::unit_z(),
             -vec3::unit_z(),
         ];
-        BounceInfo::new(ray, intersection.t, normals[intersection.i as usize])
+        let mut bounce_info =
+            BounceInfo::new(ray, intersection.t, normals[intersection.i as usize]);
+
+        // faces are mapped using the two axes they span
+        let axis = intersection.i as usize / 2;
+        let local = (bounce_info.p - self.min) / (self.max - self.min);
+        bounce_info.uv = vec2::new(local[(axis + 1) % 3], local[(axis + 2) % 3]);
+
+        bounce_info
     }
 
     fn make_bounding_box(&self) -> AABB {
         *self
     }
+
+    fn is_closed(&self) -> bool {
+        true
+    }
+
+    fn spans(&self, ray: &Ray) -> Vec<Span> {
+        // faces are numbered like in `intersect`, the way in is through the side facing the
+        // ray and the way out through the other one
+        let mut enter = Intersection {
+            t: -f32::INFINITY,
+            i: 0,
+        };
+        let mut exit = Intersection {
+            t: f32::INFINITY,
+            i: 0,
+        };
+        for a in 0..3 {
+            let inv_d = 1.0 / ray.direction[a];
+            let t0 = (self.min[a] - ray.origin[a]) * inv_d;
+            let t1 = (self.max[a] - ray.origin[a]) * inv_d;
+            let positive = ray.direction[a] >= 0.0;
+            let (close, far) = if t0 < t1 { (t0, t1) } else { (t1, t0) };
+            if close > enter.t {
+                enter = Intersection {
+                    t: close,
+                    i: positive as u32 + a as u32 * 2,
+                };
+            }
+            if far < exit.t {
+                exit = Intersection {
+                    t: far,
+                    i: !positive as u32 + a as u32 * 2,
+                };
+            }
+        }
+
+        if enter.t < exit.t {
+            vec![Span { enter, exit }]
+        } else {
+            vec![]
+        }
+    }
+
+    fn can_sample(&self) -> bool {
+        self.face_areas().iter().sum::<f32>() > 0.0
+    }
+
+    fn pdf_value(&self, origin: &vec3, dir: &vec3) -> f32 {
+        // `intersect` only finds the way in, but a point on the far side could have been
+        // sampled along the same direction too
+        let mut near = 0.0f32;
+        let mut far = f32::INFINITY;
+        let mut near_axis = 0;
+        let mut far_axis = 0;
+        for a in 0..3 {
+            let inv_d = 1.0 / dir[a];
+            let t0 = (self.min[a] - origin[a]) * inv_d;
+            let t1 = (self.max[a] - origin[a]) * inv_d;
+            let (close, away) = if t0 < t1 { (t0, t1) } else { (t1, t0) };
+            if close > near {
+                near = close;
+                near_axis = a;
+            }
+            if away < far {
+                far = away;
+                far_axis = a;
+            }
+        }
+        if far <= near {
+            return 0.0;
+        }
+
+        let area = 2.0 * self.face_areas().iter().sum::<f32>();
+        let mut pdf = 0.0;
+        for (t, axis) in [(near, near_axis), (far, far_axis)] {
+            let cosine = (dir[axis] / dir.mag()).abs();
+            if t > 0.001 && cosine > 0.0 {
+                pdf += t * t * dir.mag_sq() / (cosine * area);
+            }
+        }
+
+        pdf
+    }
+
+    fn random(&self, origin: &vec3) -> vec3 {
+        // pick a face in proportion to its area, then a point on it
+        let size = self.max - self.min;
+        let faces = self.face_areas();
+        let mut u = rand::random::<f32>() * (faces[0] + faces[1] + faces[2]);
+        let mut axis = 2;
+        for (a, face) in faces.iter().enumerate() {
+            if u < *face {
+                axis = a;
+                break;
+            }
+            u -= face;
+        }
+
+        let mut point = self.min + size * vec3::random();
+        point[axis] = if rand::random::<bool>() {
+            self.max[axis]
+        } else {
+            self.min[axis]
+        };
+
+        point - origin
+    }
 }
 
 impl FromHCY for AABB {
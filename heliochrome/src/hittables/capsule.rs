@@ -0,0 +1,165 @@
+use std::error::Error;
+use std::f32::consts::{PI, TAU};
+
+use super::{area_pdf_value, BounceInfo, Hittable, Intersection, AABB};
+use crate::{
+    loader::{parse_into, FromHCY},
+    maths::{solve_quadratic, vec2, vec3, Ray, ONB},
+};
+
+// a cylinder between `a` and `b` with a hemisphere on each end
+#[derive(Clone, Debug)]
+pub struct Capsule {
+    a: vec3,
+    b: vec3,
+    radius: f32,
+    length: f32,
+    onb: ONB,
+}
+
+impl Capsule {
+    pub fn new(a: vec3, b: vec3, radius: f32) -> Self {
+        Self {
+            a,
+            b,
+            radius,
+            length: (b - a).mag(),
+            onb: ONB::new_from_w((b - a).normalized()),
+        }
+    }
+
+    fn side_area(&self) -> f32 {
+        TAU * self.radius * self.length
+    }
+
+    fn area(&self) -> f32 {
+        self.side_area() + 4.0 * PI * self.radius * self.radius
+    }
+}
+
+impl Hittable for Capsule {
+    fn intersect(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<Intersection> {
+        let axis = self.onb.w;
+        let oa = ray.origin - self.a;
+        let mut closest: Option<f32> = None;
+        let mut consider = |t: f32| {
+            if t_min <= t && t <= t_max && closest.map_or(true, |c| t < c) {
+                closest = Some(t);
+            }
+        };
+
+        // the body, with everything along the axis projected out
+        let d_perp = ray.direction - axis * ray.direction.dot(axis);
+        let o_perp = oa - axis * oa.dot(axis);
+        if let Some((t0, t1)) = solve_quadratic(
+            d_perp.mag_sq(),
+            2.0 * o_perp.dot(d_perp),
+            o_perp.mag_sq() - self.radius * self.radius,
+        ) {
+            for t in [t0, t1] {
+                let y = (oa + ray.direction * t).dot(axis);
+                if 0.0 <= y && y <= self.length {
+                    consider(t);
+                }
+            }
+        }
+
+        // only the outer half of each end sphere is part of the surface
+        for (center, outward) in [(self.a, -1.0), (self.b, 1.0)] {
+            let oc = ray.origin - center;
+            if let Some((t0, t1)) = solve_quadratic(
+                ray.direction.mag_sq(),
+                2.0 * oc.dot(ray.direction),
+                oc.mag_sq() - self.radius * self.radius,
+            ) {
+                for t in [t0, t1] {
+                    if (oc + ray.direction * t).dot(axis) * outward >= 0.0 {
+                        consider(t);
+                    }
+                }
+            }
+        }
+
+        closest.map(|t| Intersection { t, i: 0 })
+    }
+
+    fn get_bounce_info(&self, ray: &Ray, intersection: Intersection) -> BounceInfo {
+        let p = ray.at(intersection.t);
+        let y = (p - self.a).dot(self.onb.w);
+        let on_axis = self.a + self.onb.w * y.clamp(0.0, self.length);
+        let normal = (p - on_axis) / self.radius;
+
+        let mut bounce_info = BounceInfo::new(ray, intersection.t, normal);
+        let local = self.onb.to_local(&normal);
+        bounce_info.uv = vec2::new(
+            0.5 + local.y.atan2(local.x) / TAU,
+            (y + self.radius) / (self.length + 2.0 * self.radius),
+        );
+
+        bounce_info
+    }
+
+    fn make_bounding_box(&self) -> AABB {
+        AABB::new(
+            self.a.min(&self.b) - vec3::splat(self.radius),
+            self.a.max(&self.b) + vec3::splat(self.radius),
+        )
+    }
+
+    fn can_sample(&self) -> bool {
+        true
+    }
+
+    fn pdf_value(&self, origin: &vec3, dir: &vec3) -> f32 {
+        area_pdf_value(self, self.area(), origin, dir)
+    }
+
+    fn random(&self, origin: &vec3) -> vec3 {
+        let point = if rand::random::<f32>() * self.area() < self.side_area() {
+            let phi = TAU * rand::random::<f32>();
+            self.a
+                + self.onb.local(&vec3::new(
+                    self.radius * phi.cos(),
+                    self.radius * phi.sin(),
+                    self.length * rand::random::<f32>(),
+                ))
+        } else {
+            // a point on a whole sphere, moved to whichever end it faces
+            let n = vec3::random_in_unit_sphere().normalized();
+            let center = if n.dot(self.onb.w) >= 0.0 {
+                self.b
+            } else {
+                self.a
+            };
+            center + n * self.radius
+        };
+
+        point - origin
+    }
+}
+
+impl FromHCY for Capsule {
+    fn from_hcy(_member: Option<&str>, lines: Vec<String>) -> Result<Self, Box<dyn Error>> {
+        let mut a = None;
+        let mut b = None;
+        let mut radius = None;
+
+        for line in lines.into_iter() {
+            let (key, value) = line
+                .split_once(':')
+                .ok_or("invalid key value pair syntax")?;
+            match key.trim() {
+                "a" => a = Some(parse_into(value)?),
+                "b" => b = Some(parse_into(value)?),
+                "radius" => radius = Some(parse_into(value)?),
+                _ => {}
+            }
+        }
+
+        Ok(Capsule::new(
+            a.ok_or("missing required key `a`")?,
+            b.ok_or("missing required key `b`")?,
+            radius.ok_or("missing required key `radius`")?,
+        ))
+    }
+}
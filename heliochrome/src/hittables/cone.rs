@@ -0,0 +1,175 @@
+use std::error::Error;
+use std::f32::consts::{PI, TAU};
+
+use super::{area_pdf_value, BounceInfo, Hittable, Intersection, AABB};
+use crate::{
+    loader::{parse_into, FromHCY},
+    maths::{solve_quadratic, vec2, vec3, Ray, ONB},
+};
+
+const SIDE: u32 = 0;
+const BASE: u32 = 1;
+
+#[derive(Clone, Debug)]
+pub struct Cone {
+    base: vec3,
+    height: f32,
+    radius: f32,
+    capped: bool,
+    // w points from the base to the apex
+    onb: ONB,
+}
+
+impl Cone {
+    pub fn new(base: vec3, apex: vec3, radius: f32, capped: bool) -> Self {
+        Self {
+            base,
+            height: (apex - base).mag(),
+            radius,
+            capped,
+            onb: ONB::new_from_w((apex - base).normalized()),
+        }
+    }
+
+    fn side_area(&self) -> f32 {
+        PI * self.radius * (self.radius * self.radius + self.height * self.height).sqrt()
+    }
+
+    fn area(&self) -> f32 {
+        if self.capped {
+            self.side_area() + PI * self.radius * self.radius
+        } else {
+            self.side_area()
+        }
+    }
+}
+
+impl Hittable for Cone {
+    fn intersect(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<Intersection> {
+        let o = self.onb.to_local(&(ray.origin - self.base));
+        let d = self.onb.to_local(&ray.direction);
+        let mut closest: Option<Intersection> = None;
+        let mut consider = |t: f32, i: u32| {
+            if t_min <= t && t <= t_max && closest.map_or(true, |c| t < c.t) {
+                closest = Some(Intersection { t, i });
+            }
+        };
+
+        // x^2 + y^2 = (k (h - z))^2 with k the radius over the height
+        let k2 = (self.radius / self.height).powi(2);
+        let q = self.height - o.z;
+        if let Some((t0, t1)) = solve_quadratic(
+            d.x * d.x + d.y * d.y - k2 * d.z * d.z,
+            2.0 * (o.x * d.x + o.y * d.y + k2 * q * d.z),
+            o.x * o.x + o.y * o.y - k2 * q * q,
+        ) {
+            for t in [t0, t1] {
+                let z = o.z + t * d.z;
+                if 0.0 <= z && z <= self.height {
+                    consider(t, SIDE);
+                }
+            }
+        }
+
+        if self.capped && d.z != 0.0 {
+            let t = -o.z / d.z;
+            let (x, y) = (o.x + t * d.x, o.y + t * d.y);
+            if x * x + y * y <= self.radius * self.radius {
+                consider(t, BASE);
+            }
+        }
+
+        closest
+    }
+
+    fn get_bounce_info(&self, ray: &Ray, intersection: Intersection) -> BounceInfo {
+        let p = self.onb.to_local(&(ray.at(intersection.t) - self.base));
+        let (normal, uv) = if intersection.i == SIDE {
+            let k2 = (self.radius / self.height).powi(2);
+            (
+                vec3::new(p.x, p.y, k2 * (self.height - p.z)).normalized(),
+                vec2::new(0.5 + p.y.atan2(p.x) / TAU, p.z / self.height),
+            )
+        } else {
+            (
+                vec3::new(0.0, 0.0, -1.0),
+                vec2::new(
+                    0.5 + p.x / (2.0 * self.radius),
+                    0.5 + p.y / (2.0 * self.radius),
+                ),
+            )
+        };
+
+        let mut bounce_info = BounceInfo::new(ray, intersection.t, self.onb.local(&normal));
+        bounce_info.uv = uv;
+        bounce_info
+    }
+
+    fn make_bounding_box(&self) -> AABB {
+        let axis = self.onb.w;
+        let extent = (vec3::splat(1.0) - axis * axis)
+            .max(&vec3::splat(0.0))
+            .sqrt()
+            * self.radius;
+        let apex = self.base + axis * self.height;
+        AABB::new(
+            (self.base - extent).min(&apex),
+            (self.base + extent).max(&apex),
+        )
+    }
+
+    fn can_sample(&self) -> bool {
+        true
+    }
+
+    fn pdf_value(&self, origin: &vec3, dir: &vec3) -> f32 {
+        area_pdf_value(self, self.area(), origin, dir)
+    }
+
+    fn random(&self, origin: &vec3) -> vec3 {
+        let phi = TAU * rand::random::<f32>();
+        let local = if rand::random::<f32>() * self.area() < self.side_area() {
+            // the circumference grows linearly away from the apex
+            let s = rand::random::<f32>().sqrt();
+            vec3::new(
+                s * self.radius * phi.cos(),
+                s * self.radius * phi.sin(),
+                self.height * (1.0 - s),
+            )
+        } else {
+            let r = self.radius * rand::random::<f32>().sqrt();
+            vec3::new(r * phi.cos(), r * phi.sin(), 0.0)
+        };
+
+        self.base + self.onb.local(&local) - origin
+    }
+}
+
+impl FromHCY for Cone {
+    fn from_hcy(_member: Option<&str>, lines: Vec<String>) -> Result<Self, Box<dyn Error>> {
+        let mut base = None;
+        let mut apex = None;
+        let mut radius = None;
+        let mut capped = true;
+
+        for line in lines.into_iter() {
+            let (key, value) = line
+                .split_once(':')
+                .ok_or("invalid key value pair syntax")?;
+            match key.trim() {
+                "base" => base = Some(parse_into(value)?),
+                "apex" => apex = Some(parse_into(value)?),
+                "radius" => radius = Some(parse_into(value)?),
+                "capped" => capped = parse_into(value)?,
+                _ => {}
+            }
+        }
+
+        Ok(Cone::new(
+            base.ok_or("missing required key `base`")?,
+            apex.ok_or("missing required key `apex`")?,
+            radius.ok_or("missing required key `radius`")?,
+            capped,
+        ))
+    }
+}
@@ -0,0 +1,310 @@
+use std::borrow::Cow;
+use std::error::Error;
+
+use super::{BounceInfo, Hittable, HittableObject, Intersection, AABB};
+use crate::{
+    loader::{collect_nested_item, FromHCY},
+    maths::Ray,
+    transform::Transform,
+};
+
+// a stretch of a ray spent inside a closed shape, from where it enters to where it leaves
+#[derive(Debug, Clone, Copy)]
+pub struct Span {
+    pub enter: Intersection,
+    pub exit: Intersection,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CSGOp {
+    Union,
+    Intersection,
+    Difference,
+}
+
+// boolean combination of two closed hittables, traced exactly by combining the spans the ray
+// spends inside each of them. intersections remember which side they came from in their
+// lowest bit so the bounce info can be handed back to it.
+#[derive(Clone, Debug)]
+pub struct CSG {
+    pub op: CSGOp,
+    a: Box<HittableObject>,
+    b: Box<HittableObject>,
+    // places each side within the combined shape
+    transforms: [Option<Transform>; 2],
+    bounds: AABB,
+}
+
+impl CSG {
+    // fails if either side isn't closed
+    pub fn new<A: Into<HittableObject>, B: Into<HittableObject>>(
+        op: CSGOp,
+        a: A,
+        b: B,
+    ) -> Result<Self, Box<dyn Error>> {
+        Self::new_transformed(op, (a, None), (b, None))
+    }
+
+    pub fn new_transformed<A: Into<HittableObject>, B: Into<HittableObject>>(
+        op: CSGOp,
+        (a, a_transform): (A, Option<Transform>),
+        (b, b_transform): (B, Option<Transform>),
+    ) -> Result<Self, Box<dyn Error>> {
+        let a: HittableObject = a.into();
+        let b: HittableObject = b.into();
+        if !a.is_closed() || !b.is_closed() {
+            Err("csg only works on closed shapes")?;
+        }
+
+        let mut csg = Self {
+            op,
+            a: Box::new(a),
+            b: Box::new(b),
+            transforms: [a_transform, b_transform],
+            bounds: AABB::default(),
+        };
+        let (box_a, box_b) = (csg.side_bounds(0), csg.side_bounds(1));
+        csg.bounds = match op {
+            CSGOp::Union => AABB::surrounding(&box_a, &box_b),
+            CSGOp::Intersection => AABB::intersection(&box_a, &box_b),
+            CSGOp::Difference => box_a,
+        };
+
+        Ok(csg)
+    }
+
+    fn side(&self, side: u32) -> &HittableObject {
+        if side == 0 {
+            &self.a
+        } else {
+            &self.b
+        }
+    }
+
+    fn side_bounds(&self, side: u32) -> AABB {
+        let aabb = self.side(side).make_bounding_box();
+        match &self.transforms[side as usize] {
+            Some(transform) => transform.trans_aabb(&aabb),
+            None => aabb,
+        }
+    }
+
+    // the ray in the space of one side, transforms keep distances along it the same
+    fn side_ray<'a>(&'a self, side: u32, ray: &Ray) -> (Ray, Option<Cow<'a, Transform>>) {
+        match &self.transforms[side as usize] {
+            Some(transform) => {
+                let transform = transform.at(ray.time);
+                (transform.trans_ray(ray), Some(transform))
+            }
+            None => (*ray, None),
+        }
+    }
+
+    fn inside(&self, in_a: bool, in_b: bool) -> bool {
+        match self.op {
+            CSGOp::Union => in_a || in_b,
+            CSGOp::Intersection => in_a && in_b,
+            CSGOp::Difference => in_a && !in_b,
+        }
+    }
+}
+
+impl Hittable for CSG {
+    fn intersect(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<Intersection> {
+        if !self.bounds.hits(ray, t_min, t_max) {
+            return None;
+        }
+
+        // spans are in order, so the first crossing in range is the closest
+        self.spans(ray)
+            .into_iter()
+            .flat_map(|span| [span.enter, span.exit])
+            .find(|hit| t_min <= hit.t && hit.t <= t_max)
+    }
+
+    fn get_bounce_info(&self, ray: &Ray, intersection: Intersection) -> BounceInfo {
+        let from_b = intersection.i & 1 == 1;
+        let child = Intersection {
+            t: intersection.t,
+            i: intersection.i >> 1,
+        };
+
+        let side = from_b as u32;
+        let (r, transform) = self.side_ray(side, ray);
+        let mut bounce_info = self.side(side).get_bounce_info(&r, child);
+        if let Some(transform) = transform {
+            // the normal still faces the ray, so which side was hit stays the same
+            bounce_info.p = ray.at(bounce_info.t);
+            bounce_info.normal = transform.trans_normal(&bounce_info.normal);
+        }
+
+        // the surface of a hole faces into the shape it was cut from
+        if from_b && self.op == CSGOp::Difference {
+            bounce_info.front_face = !bounce_info.front_face;
+        }
+        bounce_info
+    }
+
+    fn make_bounding_box(&self) -> AABB {
+        self.bounds
+    }
+
+    fn is_closed(&self) -> bool {
+        true
+    }
+
+    fn spans(&self, ray: &Ray) -> Vec<Span> {
+        let tag = |hit: Intersection, side: u32| Intersection {
+            t: hit.t,
+            i: hit.i << 1 | side,
+        };
+
+        // every crossing of either shape, with the side it belongs to and whether it enters
+        let mut events = vec![];
+        for side in [0, 1] {
+            let (r, _) = self.side_ray(side, ray);
+            for span in self.side(side).spans(&r) {
+                events.push((tag(span.enter, side), side, true));
+                events.push((tag(span.exit, side), side, false));
+            }
+        }
+        events.sort_by(|a, b| a.0.t.total_cmp(&b.0.t));
+
+        let mut spans = vec![];
+        let mut within = [false; 2];
+        let mut enter = None;
+        for (hit, side, entering) in events {
+            let was_inside = self.inside(within[0], within[1]);
+            within[side as usize] = entering;
+            match (was_inside, self.inside(within[0], within[1])) {
+                (false, true) => enter = Some(hit),
+                (true, false) => {
+                    if let Some(enter) = enter.take() {
+                        spans.push(Span { enter, exit: hit });
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        spans
+    }
+}
+
+impl FromHCY for CSG {
+    fn from_hcy(member: Option<&str>, lines: Vec<String>) -> Result<Self, Box<dyn Error>> {
+        let op = match member.ok_or("invalid syntax missing csg operation")?.trim() {
+            "union" => CSGOp::Union,
+            "intersection" => CSGOp::Intersection,
+            "difference" => CSGOp::Difference,
+            op => Err(format!("unknown csg operation {op}"))?,
+        };
+
+        let mut a = None;
+        let mut b = None;
+        let mut transforms = [None, None];
+
+        let mut line_iter = lines.iter();
+        while let Some(line) = line_iter.next() {
+            let (key, value) = line
+                .split_once(':')
+                .ok_or("invalid key value pair syntax")?;
+            let side = match key.trim() {
+                "a" => &mut a,
+                "b" => &mut b,
+                key @ ("a transform" | "b transform") => {
+                    transforms[key.starts_with('b') as usize] = Some(
+                        Transform::from_hcy(None, collect_nested_item(line, &mut line_iter))
+                            .map_err(|err| format!("could not parse {key} key: {err}"))?,
+                    );
+                    continue;
+                }
+                _ => continue,
+            };
+
+            let hittable = HittableObject::from_hcy(
+                Some(value.trim()),
+                collect_nested_item(line, &mut line_iter),
+            )
+            .map_err(|err| format!("could not parse {} key: {err}", key.trim()))?;
+            if !hittable.is_closed() {
+                Err(format!(
+                    "csg {} is not a closed shape, only spheres, boxes and other csg shapes are",
+                    key.trim()
+                ))?;
+            }
+            *side = Some(hittable);
+        }
+
+        let [a_transform, b_transform] = transforms;
+        CSG::new_transformed(
+            op,
+            (a.ok_or("missing required key `a`")?, a_transform),
+            (b.ok_or("missing required key `b`")?, b_transform),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        hittables::{Rect, Sphere},
+        maths::{mat4, vec3},
+    };
+
+    #[test]
+    fn traces_lenses_and_holes() {
+        let ray = Ray::new(vec3::new(-5.0, 0.0, 0.0), vec3::unit_x());
+
+        // a lens where two unit spheres overlap between x = -0.5 and 0.5
+        let lens = CSG::new(
+            CSGOp::Intersection,
+            Sphere::new(vec3::new(-0.5, 0.0, 0.0), 1.0),
+            Sphere::new(vec3::new(0.5, 0.0, 0.0), 1.0),
+        )
+        .unwrap();
+        let hit = lens.intersect(&ray, 0.001, f32::INFINITY).unwrap();
+        assert!((hit.t - 4.5).abs() < 0.001);
+        let exit = lens.intersect(&ray, hit.t + 0.001, f32::INFINITY).unwrap();
+        assert!((exit.t - 5.5).abs() < 0.001);
+
+        // a box with a spherical hole through the middle of it
+        let holed = CSG::new(
+            CSGOp::Difference,
+            AABB::new(vec3::splat(-1.0), vec3::splat(1.0)),
+            Sphere::new(vec3::splat(0.0), 0.5),
+        )
+        .unwrap();
+        let spans = holed.spans(&ray);
+        assert_eq!(spans.len(), 2);
+        assert!((spans[0].exit.t - 4.5).abs() < 0.001);
+
+        // the wall of the hole faces into it, towards the ray coming out of the box
+        let bounce = holed.get_bounce_info(&ray, spans[0].exit);
+        assert!(!bounce.front_face);
+        assert!((bounce.normal - vec3::new(-1.0, 0.0, 0.0)).mag() < 0.001);
+        let bounce = holed.get_bounce_info(&ray, spans[1].enter);
+        assert!(bounce.front_face);
+
+        // a square tunnel along x, turned to run along z so the ray crosses its walls
+        let tunnel = CSG::new_transformed(
+            CSGOp::Difference,
+            (AABB::new(vec3::splat(-1.0), vec3::splat(1.0)), None),
+            (
+                AABB::new(vec3::new(-2.0, -0.25, -0.25), vec3::new(2.0, 0.25, 0.25)),
+                Some(Transform::new(mat4::rotate_deg(vec3::new(0.0, 90.0, 0.0)))),
+            ),
+        )
+        .unwrap();
+        let spans = tunnel.spans(&ray);
+        assert_eq!(spans.len(), 2);
+        assert!((spans[0].exit.t - 4.75).abs() < 0.001);
+        let bounce = tunnel.get_bounce_info(&ray, spans[0].exit);
+        assert!((bounce.p - vec3::new(-0.25, 0.0, 0.0)).mag() < 0.001);
+        assert!((bounce.normal - vec3::new(-1.0, 0.0, 0.0)).mag() < 0.001);
+
+        let rect = Rect::new(vec3::splat(0.0), vec3::unit_x(), vec3::unit_y());
+        assert!(CSG::new(CSGOp::Union, lens, rect).is_err());
+    }
+}
@@ -0,0 +1,172 @@
+use std::error::Error;
+use std::f32::consts::{PI, TAU};
+
+use super::{area_pdf_value, BounceInfo, Hittable, Intersection, AABB};
+use crate::{
+    loader::{parse_into, FromHCY},
+    maths::{solve_quadratic, vec2, vec3, Ray, ONB},
+};
+
+const SIDE: u32 = 0;
+const BOTTOM: u32 = 1;
+const TOP: u32 = 2;
+
+#[derive(Clone, Debug)]
+pub struct Cylinder {
+    base: vec3,
+    height: f32,
+    radius: f32,
+    capped: bool,
+    // w points from the base to the top
+    onb: ONB,
+}
+
+impl Cylinder {
+    pub fn new(base: vec3, top: vec3, radius: f32, capped: bool) -> Self {
+        Self {
+            base,
+            height: (top - base).mag(),
+            radius,
+            capped,
+            onb: ONB::new_from_w((top - base).normalized()),
+        }
+    }
+
+    fn area(&self) -> f32 {
+        let side = TAU * self.radius * self.height;
+        if self.capped {
+            side + 2.0 * PI * self.radius * self.radius
+        } else {
+            side
+        }
+    }
+}
+
+impl Hittable for Cylinder {
+    fn intersect(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<Intersection> {
+        let o = self.onb.to_local(&(ray.origin - self.base));
+        let d = self.onb.to_local(&ray.direction);
+        let mut closest: Option<Intersection> = None;
+        let mut consider = |t: f32, i: u32| {
+            if t_min <= t && t <= t_max && closest.map_or(true, |c| t < c.t) {
+                closest = Some(Intersection { t, i });
+            }
+        };
+
+        if let Some((t0, t1)) = solve_quadratic(
+            d.x * d.x + d.y * d.y,
+            2.0 * (o.x * d.x + o.y * d.y),
+            o.x * o.x + o.y * o.y - self.radius * self.radius,
+        ) {
+            for t in [t0, t1] {
+                let z = o.z + t * d.z;
+                if 0.0 <= z && z <= self.height {
+                    consider(t, SIDE);
+                }
+            }
+        }
+
+        if self.capped && d.z != 0.0 {
+            for (z, i) in [(0.0, BOTTOM), (self.height, TOP)] {
+                let t = (z - o.z) / d.z;
+                let (x, y) = (o.x + t * d.x, o.y + t * d.y);
+                if x * x + y * y <= self.radius * self.radius {
+                    consider(t, i);
+                }
+            }
+        }
+
+        closest
+    }
+
+    fn get_bounce_info(&self, ray: &Ray, intersection: Intersection) -> BounceInfo {
+        let p = self.onb.to_local(&(ray.at(intersection.t) - self.base));
+        let (normal, uv) = match intersection.i {
+            SIDE => (
+                vec3::new(p.x, p.y, 0.0) / self.radius,
+                vec2::new(0.5 + p.y.atan2(p.x) / TAU, p.z / self.height),
+            ),
+            i => (
+                vec3::new(0.0, 0.0, if i == TOP { 1.0 } else { -1.0 }),
+                vec2::new(
+                    0.5 + p.x / (2.0 * self.radius),
+                    0.5 + p.y / (2.0 * self.radius),
+                ),
+            ),
+        };
+
+        let mut bounce_info = BounceInfo::new(ray, intersection.t, self.onb.local(&normal));
+        bounce_info.uv = uv;
+        bounce_info
+    }
+
+    fn make_bounding_box(&self) -> AABB {
+        // each end is a disk, which extends radius * sin(angle to the axis) along every axis
+        let axis = self.onb.w;
+        let extent = (vec3::splat(1.0) - axis * axis)
+            .max(&vec3::splat(0.0))
+            .sqrt()
+            * self.radius;
+        let top = self.base + axis * self.height;
+        AABB::new(self.base.min(&top) - extent, self.base.max(&top) + extent)
+    }
+
+    fn can_sample(&self) -> bool {
+        true
+    }
+
+    fn pdf_value(&self, origin: &vec3, dir: &vec3) -> f32 {
+        area_pdf_value(self, self.area(), origin, dir)
+    }
+
+    fn random(&self, origin: &vec3) -> vec3 {
+        let phi = TAU * rand::random::<f32>();
+        let side = TAU * self.radius * self.height;
+        let local = if rand::random::<f32>() * self.area() < side {
+            vec3::new(
+                self.radius * phi.cos(),
+                self.radius * phi.sin(),
+                self.height * rand::random::<f32>(),
+            )
+        } else {
+            let r = self.radius * rand::random::<f32>().sqrt();
+            let z = if rand::random::<bool>() {
+                self.height
+            } else {
+                0.0
+            };
+            vec3::new(r * phi.cos(), r * phi.sin(), z)
+        };
+
+        self.base + self.onb.local(&local) - origin
+    }
+}
+
+impl FromHCY for Cylinder {
+    fn from_hcy(_member: Option<&str>, lines: Vec<String>) -> Result<Self, Box<dyn Error>> {
+        let mut base = None;
+        let mut top = None;
+        let mut radius = None;
+        let mut capped = true;
+
+        for line in lines.into_iter() {
+            let (key, value) = line
+                .split_once(':')
+                .ok_or("invalid key value pair syntax")?;
+            match key.trim() {
+                "base" => base = Some(parse_into(value)?),
+                "top" => top = Some(parse_into(value)?),
+                "radius" => radius = Some(parse_into(value)?),
+                "capped" => capped = parse_into(value)?,
+                _ => {}
+            }
+        }
+
+        Ok(Cylinder::new(
+            base.ok_or("missing required key `base`")?,
+            top.ok_or("missing required key `top`")?,
+            radius.ok_or("missing required key `radius`")?,
+            capped,
+        ))
+    }
+}
@@ -0,0 +1,108 @@
+use std::error::Error;
+use std::f32::consts::{PI, TAU};
+
+use super::{area_pdf_value, BounceInfo, Hittable, Intersection, AABB};
+use crate::{
+    loader::{parse_into, FromHCY},
+    maths::{vec2, vec3, Ray, ONB},
+};
+
+#[derive(Clone, Debug)]
+pub struct Disk {
+    center: vec3,
+    radius: f32,
+    onb: ONB,
+}
+
+impl Disk {
+    pub fn new(center: vec3, normal: vec3, radius: f32) -> Self {
+        Self {
+            center,
+            radius,
+            onb: ONB::new_from_w(normal.normalized()),
+        }
+    }
+}
+
+impl Hittable for Disk {
+    fn intersect(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<Intersection> {
+        let d = ray.direction.dot(self.onb.w);
+        if d.abs() < 0.0001 {
+            return None;
+        }
+
+        let t = (self.center - ray.origin).dot(self.onb.w) / d;
+        if t < t_min || t_max < t || (ray.at(t) - self.center).mag_sq() > self.radius * self.radius
+        {
+            return None;
+        }
+
+        Some(Intersection { t, i: 0 })
+    }
+
+    fn get_bounce_info(&self, ray: &Ray, intersection: Intersection) -> BounceInfo {
+        let mut bounce_info = BounceInfo::new(ray, intersection.t, self.onb.w);
+        let p = self.onb.to_local(&(bounce_info.p - self.center));
+        bounce_info.uv = vec2::new(
+            0.5 + p.x / (2.0 * self.radius),
+            0.5 + p.y / (2.0 * self.radius),
+        );
+
+        bounce_info
+    }
+
+    fn make_bounding_box(&self) -> AABB {
+        let normal = self.onb.w;
+        // padded so axis aligned disks don't get a flat box
+        let extent = (vec3::splat(1.0) - normal * normal)
+            .max(&vec3::splat(0.0))
+            .sqrt()
+            * self.radius
+            + vec3::splat(0.001);
+        AABB::new(self.center - extent, self.center + extent)
+    }
+
+    fn can_sample(&self) -> bool {
+        true
+    }
+
+    fn pdf_value(&self, origin: &vec3, dir: &vec3) -> f32 {
+        area_pdf_value(self, PI * self.radius * self.radius, origin, dir)
+    }
+
+    fn random(&self, origin: &vec3) -> vec3 {
+        let r = self.radius * rand::random::<f32>().sqrt();
+        let phi = TAU * rand::random::<f32>();
+        self.center
+            + self
+                .onb
+                .local(&vec3::new(r * phi.cos(), r * phi.sin(), 0.0))
+            - origin
+    }
+}
+
+impl FromHCY for Disk {
+    fn from_hcy(_member: Option<&str>, lines: Vec<String>) -> Result<Self, Box<dyn Error>> {
+        let mut center = None;
+        let mut normal = None;
+        let mut radius = None;
+
+        for line in lines.into_iter() {
+            let (key, value) = line
+                .split_once(':')
+                .ok_or("invalid key value pair syntax")?;
+            match key.trim() {
+                "center" => center = Some(parse_into(value)?),
+                "normal" => normal = Some(parse_into(value)?),
+                "radius" => radius = Some(parse_into(value)?),
+                _ => {}
+            }
+        }
+
+        Ok(Disk::new(
+            center.ok_or("missing required key `center`")?,
+            normal.ok_or("missing required key `normal`")?,
+            radius.ok_or("missing required key `radius`")?,
+        ))
+    }
+}
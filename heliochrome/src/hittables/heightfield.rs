@@ -0,0 +1,388 @@
+use std::{error::Error, path::Path};
+
+use super::{BounceInfo, Hittable, Intersection, Triangle, AABB};
+use crate::{
+    image::Image,
+    loader::{parse_into, FromHCY},
+    maths::{fbm, vec2, vec3, Ray, NOISE_MAX},
+};
+
+// the lowest and highest point in each block of cells, every level halves the resolution
+#[derive(Clone, Debug)]
+struct MinMaxLevel {
+    width: usize,
+    depth: usize,
+    ranges: Vec<(f32, f32)>,
+}
+
+// a grid of heights over the xz plane, each cell split into two triangles. rays descend a
+// quadtree of min max boxes over the cells, so only the few cells near the ray are tested.
+#[derive(Clone, Debug)]
+pub struct Heightfield {
+    // samples along x and z
+    width: usize,
+    depth: usize,
+    heights: Vec<f32>,
+    normals: Vec<vec3>,
+    // the corner at the lowest possible height and the extent, heights of 0 to 1 span the y
+    min: vec3,
+    size: vec3,
+    levels: Vec<MinMaxLevel>,
+}
+
+impl Heightfield {
+    pub fn new(heights: Vec<f32>, width: usize, depth: usize, min: vec3, size: vec3) -> Self {
+        assert!(
+            width >= 2 && depth >= 2,
+            "a heightfield needs at least one cell"
+        );
+        assert_eq!(heights.len(), width * depth);
+        let heights = heights.into_iter().map(|h| min.y + h * size.y).collect();
+
+        let mut heightfield = Self {
+            width,
+            depth,
+            heights,
+            normals: vec![],
+            min,
+            size,
+            levels: vec![],
+        };
+        heightfield.normals = (0..depth)
+            .flat_map(|j| (0..width).map(move |i| (i, j)))
+            .map(|(i, j)| heightfield.vertex_normal(i, j))
+            .collect();
+        heightfield.build_levels();
+        heightfield
+    }
+
+    // heights from the brightness of an image, which is stretched over the whole field
+    pub fn from_image(image: &Image, min: vec3, size: vec3) -> Self {
+        let (width, depth) = (image.size.x as usize, image.size.y as usize);
+        let heights = image.buffer.iter().map(|c| c.luminance()).collect();
+        Self::new(heights, width, depth, min, size)
+    }
+
+    // rolling hills of fractal noise, `frequency` is the number of features along x
+    pub fn from_noise(
+        width: usize,
+        depth: usize,
+        octaves: u32,
+        frequency: f32,
+        min: vec3,
+        size: vec3,
+    ) -> Self {
+        let scale = frequency / (width - 1) as f32;
+        let heights = (0..depth)
+            .flat_map(|j| (0..width).map(move |i| (i, j)))
+            .map(|(i, j)| {
+                let h = fbm(vec3::new(i as f32 * scale, 0.5, j as f32 * scale), octaves);
+                // the octaves add up to at most twice the first one
+                (0.5 + 0.25 * h / NOISE_MAX).clamp(0.0, 1.0)
+            })
+            .collect();
+        Self::new(heights, width, depth, min, size)
+    }
+
+    fn cell_size(&self) -> vec2 {
+        vec2::new(
+            self.size.x / (self.width - 1) as f32,
+            self.size.z / (self.depth - 1) as f32,
+        )
+    }
+
+    fn height(&self, i: usize, j: usize) -> f32 {
+        self.heights[i + j * self.width]
+    }
+
+    fn vertex(&self, i: usize, j: usize) -> vec3 {
+        let cell = self.cell_size();
+        vec3::new(
+            self.min.x + i as f32 * cell.x,
+            self.height(i, j),
+            self.min.z + j as f32 * cell.y,
+        )
+    }
+
+    // central differences, one sided along the border
+    fn vertex_normal(&self, i: usize, j: usize) -> vec3 {
+        let cell = self.cell_size();
+        let (i0, i1) = (i.saturating_sub(1), (i + 1).min(self.width - 1));
+        let (j0, j1) = (j.saturating_sub(1), (j + 1).min(self.depth - 1));
+        let dx = (self.height(i1, j) - self.height(i0, j)) / ((i1 - i0) as f32 * cell.x);
+        let dz = (self.height(i, j1) - self.height(i, j0)) / ((j1 - j0) as f32 * cell.y);
+        vec3::new(-dx, 1.0, -dz).normalized()
+    }
+
+    fn build_levels(&mut self) {
+        let (mut width, mut depth) = (self.width - 1, self.depth - 1);
+        let mut ranges = Vec::with_capacity(width * depth);
+        for j in 0..depth {
+            for i in 0..width {
+                let corners = [
+                    self.height(i, j),
+                    self.height(i + 1, j),
+                    self.height(i, j + 1),
+                    self.height(i + 1, j + 1),
+                ];
+                ranges.push((
+                    corners.into_iter().fold(f32::INFINITY, f32::min),
+                    corners.into_iter().fold(-f32::INFINITY, f32::max),
+                ));
+            }
+        }
+        self.levels.push(MinMaxLevel {
+            width,
+            depth,
+            ranges,
+        });
+
+        while width > 1 || depth > 1 {
+            let below = self.levels.last().unwrap();
+            // a cell of the next level covers two of this one, rounding up
+            let (next_width, next_depth) = (width / 2 + width % 2, depth / 2 + depth % 2);
+            let mut ranges = Vec::with_capacity(next_width * next_depth);
+            for j in 0..next_depth {
+                for i in 0..next_width {
+                    let mut range = (f32::INFINITY, -f32::INFINITY);
+                    for (ci, cj) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                        let (ci, cj) = (i * 2 + ci, j * 2 + cj);
+                        if ci < width && cj < depth {
+                            let (lo, hi) = below.ranges[ci + cj * width];
+                            range = (range.0.min(lo), range.1.max(hi));
+                        }
+                    }
+                    ranges.push(range);
+                }
+            }
+            (width, depth) = (next_width, next_depth);
+            self.levels.push(MinMaxLevel {
+                width,
+                depth,
+                ranges,
+            });
+        }
+    }
+
+    // the box around block (i, j) of a level, covering 2^level cells a side
+    fn node_bounds(&self, level: usize, i: usize, j: usize) -> AABB {
+        let cell = self.cell_size();
+        let (lo, hi) = self.levels[level].ranges[i + j * self.levels[level].width];
+        let span = 1 << level;
+        let (i0, j0) = (i * span, j * span);
+        let i1 = ((i + 1) * span).min(self.width - 1);
+        let j1 = ((j + 1) * span).min(self.depth - 1);
+        // padded so flat blocks still have some thickness to clip against
+        AABB::new(
+            vec3::new(
+                self.min.x + i0 as f32 * cell.x,
+                lo,
+                self.min.z + j0 as f32 * cell.y,
+            ) - vec3::splat(0.0001),
+            vec3::new(
+                self.min.x + i1 as f32 * cell.x,
+                hi,
+                self.min.z + j1 as f32 * cell.y,
+            ) + vec3::splat(0.0001),
+        )
+    }
+
+    fn cell_triangles(&self, i: usize, j: usize) -> [Triangle; 2] {
+        let (p00, p10) = (self.vertex(i, j), self.vertex(i + 1, j));
+        let (p01, p11) = (self.vertex(i, j + 1), self.vertex(i + 1, j + 1));
+        [
+            Triangle::new([p00, p10, p11]),
+            Triangle::new([p00, p11, p01]),
+        ]
+    }
+
+    fn intersect_node(
+        &self,
+        level: usize,
+        i: usize,
+        j: usize,
+        ray: &Ray,
+        t_min: f32,
+        mut t_max: f32,
+    ) -> Option<Intersection> {
+        self.node_bounds(level, i, j).clip(ray, t_min, t_max)?;
+
+        if level == 0 {
+            let mut closest = None;
+            for (k, tri) in self.cell_triangles(i, j).iter().enumerate() {
+                if let Some(hit) = tri.intersect(ray, t_min, t_max) {
+                    t_max = hit.t;
+                    closest = Some(Intersection {
+                        t: hit.t,
+                        i: ((i + j * (self.width - 1)) * 2 + k) as u32,
+                    });
+                }
+            }
+            return closest;
+        }
+
+        // children nearer to the ray origin first, so later ones are clipped by their hits
+        let below = &self.levels[level - 1];
+        let xs = if ray.direction.x >= 0.0 {
+            [0, 1]
+        } else {
+            [1, 0]
+        };
+        let zs = if ray.direction.z >= 0.0 {
+            [0, 1]
+        } else {
+            [1, 0]
+        };
+        let mut closest = None;
+        for cj in zs {
+            for ci in xs {
+                let (ci, cj) = (i * 2 + ci, j * 2 + cj);
+                if ci >= below.width || cj >= below.depth {
+                    continue;
+                }
+                if let Some(hit) = self.intersect_node(level - 1, ci, cj, ray, t_min, t_max) {
+                    t_max = hit.t;
+                    closest = Some(hit);
+                }
+            }
+        }
+
+        closest
+    }
+}
+
+impl Hittable for Heightfield {
+    fn intersect(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<Intersection> {
+        self.intersect_node(self.levels.len() - 1, 0, 0, ray, t_min, t_max)
+    }
+
+    fn get_bounce_info(&self, ray: &Ray, intersection: Intersection) -> BounceInfo {
+        let cell_idx = intersection.i as usize / 2;
+        let (i, j) = (cell_idx % (self.width - 1), cell_idx / (self.width - 1));
+        let p = ray.at(intersection.t);
+
+        // shading normals blend the vertex normals across the cell
+        let cell = self.cell_size();
+        let fx = ((p.x - self.min.x) / cell.x - i as f32).clamp(0.0, 1.0);
+        let fz = ((p.z - self.min.z) / cell.y - j as f32).clamp(0.0, 1.0);
+        let normal = |i: usize, j: usize| self.normals[i + j * self.width];
+        let normal = (normal(i, j) * (1.0 - fx) + normal(i + 1, j) * fx) * (1.0 - fz)
+            + (normal(i, j + 1) * (1.0 - fx) + normal(i + 1, j + 1) * fx) * fz;
+
+        let mut bounce_info = BounceInfo::new(ray, intersection.t, normal.normalized());
+        bounce_info.uv = vec2::new(
+            (p.x - self.min.x) / self.size.x,
+            (p.z - self.min.z) / self.size.z,
+        );
+        bounce_info
+    }
+
+    fn make_bounding_box(&self) -> AABB {
+        self.node_bounds(self.levels.len() - 1, 0, 0)
+    }
+}
+
+impl FromHCY for Heightfield {
+    fn from_hcy(_member: Option<&str>, lines: Vec<String>) -> Result<Self, Box<dyn Error>> {
+        let mut path = None;
+        let mut min = None;
+        let mut size = None;
+        let mut resolution: usize = 512;
+        let mut octaves = 6;
+        let mut frequency = 4.0;
+
+        for line in lines.into_iter() {
+            let (key, value) = line
+                .split_once(':')
+                .ok_or("invalid key value pair syntax")?;
+            match key.trim() {
+                "path" => path = Some(value.trim().to_owned()),
+                "min" => min = Some(parse_into(value)?),
+                "size" => size = Some(parse_into(value)?),
+                "resolution" => resolution = parse_into(value)?,
+                "octaves" => octaves = parse_into(value)?,
+                "frequency" => frequency = parse_into(value)?,
+                _ => {}
+            }
+        }
+
+        let min = min.ok_or("missing required key `min`")?;
+        let size: vec3 = size.ok_or("missing required key `size`")?;
+        // an image if there is one, otherwise noise with square cells
+        if let Some(path) = path {
+            let image = Image::load_linear(Path::new(&path))?;
+            if image.size.x < 2.0 || image.size.y < 2.0 {
+                Err("heightfield images need to be at least 2x2 pixels")?;
+            }
+            Ok(Heightfield::from_image(&image, min, size))
+        } else {
+            if resolution < 2 {
+                Err("heightfield resolution needs to be at least 2")?;
+            }
+            let depth = ((resolution - 1) as f32 * size.z / size.x).round() as usize + 1;
+            Ok(Heightfield::from_noise(
+                resolution,
+                depth.max(2),
+                octaves,
+                frequency,
+                min,
+                size,
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn quadtree_finds_the_closest_triangle() {
+        let field = Heightfield::from_noise(
+            37,
+            21,
+            4,
+            3.0,
+            vec3::new(-2.0, 0.0, -1.0),
+            vec3::new(4.0, 1.0, 2.0),
+        );
+
+        for _ in 0..500 {
+            let origin = vec3::new(
+                rand::random::<f32>() * 6.0 - 3.0,
+                2.0,
+                rand::random::<f32>() * 4.0 - 2.0,
+            );
+            let target = vec3::new(
+                rand::random::<f32>() * 4.0 - 2.0,
+                rand::random::<f32>(),
+                rand::random::<f32>() * 2.0 - 1.0,
+            );
+            let ray = Ray::new(origin, target - origin);
+
+            let brute_force = (0..20)
+                .flat_map(|j| (0..36).map(move |i| (i, j)))
+                .flat_map(|(i, j)| field.cell_triangles(i, j))
+                .filter_map(|tri| tri.intersect(&ray, 0.001, f32::INFINITY))
+                .map(|hit| hit.t)
+                .min_by(|a, b| a.total_cmp(b));
+            let hit = field.intersect(&ray, 0.001, f32::INFINITY).map(|hit| hit.t);
+            assert_eq!(hit, brute_force);
+        }
+
+        // straight down onto a ramp gives its exact height, normal and uv
+        let ramp = Heightfield::new(
+            (0..3).flat_map(|_| [0.0, 0.5, 1.0]).collect(),
+            3,
+            3,
+            vec3::splat(0.0),
+            vec3::new(2.0, 1.0, 2.0),
+        );
+        let ray = Ray::new(vec3::new(0.5, 5.0, 1.5), -vec3::unit_y());
+        let hit = ramp.intersect(&ray, 0.001, f32::INFINITY).unwrap();
+        let bounce = ramp.get_bounce_info(&ray, hit);
+        assert!((bounce.p.y - 0.25).abs() < 0.0001);
+        assert!((bounce.normal - vec3::new(-0.5, 1.0, 0.0).normalized()).mag() < 0.0001);
+        assert!((bounce.uv.x - 0.25).abs() < 0.0001 && (bounce.uv.y - 0.75).abs() < 0.0001);
+    }
+}
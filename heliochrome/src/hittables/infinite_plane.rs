@@ -1,20 +1,27 @@
 use std::error::Error;
 
-use super::{BounceInfo, Hittable, Intersection, AABB};
+use super::{area_pdf_value, BounceInfo, Hittable, Intersection, AABB};
 use crate::{
     loader::{parse_into, FromHCY},
-    maths::{vec3, Ray, ONB},
+    maths::{vec2, vec3, Ray, ONB},
 };
 
 #[derive(Clone, Debug)]
 pub struct InfinitePlane {
     pub origin: vec3,
     pub normal: vec3,
+    // half the size of the plane along its uv axes if it's cut down to a rectangle, which
+    // also makes it possible to sample as a light
+    pub extent: Option<vec2>,
 }
 
 impl InfinitePlane {
     pub fn new(origin: vec3, normal: vec3) -> Self {
-        InfinitePlane { origin, normal }
+        InfinitePlane {
+            origin,
+            normal,
+            extent: None,
+        }
     }
 }
 
@@ -24,6 +31,13 @@ impl Hittable for InfinitePlane {
         if d.abs() >= 0.0001 {
             let t = (self.origin - ray.origin).dot(self.normal) / d;
             if t_min <= t && t <= t_max {
+                if let Some(extent) = self.extent {
+                    let onb = ONB::new_from_w(self.normal);
+                    let local = ray.at(t) - self.origin;
+                    if local.dot(onb.u).abs() > extent.x || local.dot(onb.v).abs() > extent.y {
+                        return None;
+                    }
+                }
                 return Some(Intersection { t, i: 0 });
             }
         }
@@ -32,24 +46,54 @@ impl Hittable for InfinitePlane {
     }
 
     fn get_bounce_info(&self, ray: &Ray, intersection: Intersection) -> BounceInfo {
-        BounceInfo::new(ray, intersection.t, self.normal)
+        let mut bounce_info = BounceInfo::new(ray, intersection.t, self.normal);
+        let onb = ONB::new_from_w(self.normal);
+        let local = bounce_info.p - self.origin;
+        bounce_info.uv = vec2::new(local.dot(onb.u), local.dot(onb.v));
+
+        bounce_info
     }
 
     fn make_bounding_box(&self) -> AABB {
         // this lets the bounding box be tight if the normal is axis aligned.
         let onb = ONB::new_from_w(self.normal);
-        let v = ((onb.v + onb.u) * f32::INFINITY).un_nan().abs();
+        let v = match self.extent {
+            Some(extent) => (onb.u * extent.x).abs() + (onb.v * extent.y).abs(),
+            None => ((onb.v + onb.u) * f32::INFINITY).un_nan().abs(),
+        };
         AABB::new(
             -v + self.origin - vec3::splat(0.0001),
             v + self.origin + vec3::splat(0.0001),
         )
     }
+
+    fn can_sample(&self) -> bool {
+        self.extent.is_some()
+    }
+
+    fn pdf_value(&self, origin: &vec3, dir: &vec3) -> f32 {
+        let Some(extent) = self.extent else {
+            return 0.0;
+        };
+        area_pdf_value(self, 4.0 * extent.x * extent.y, origin, dir)
+    }
+
+    fn random(&self, origin: &vec3) -> vec3 {
+        let Some(extent) = self.extent else {
+            return self.normal;
+        };
+        let onb = ONB::new_from_w(self.normal);
+        let (u, v) = (rand::random::<f32>(), rand::random::<f32>());
+        self.origin + onb.u * ((2.0 * u - 1.0) * extent.x) + onb.v * ((2.0 * v - 1.0) * extent.y)
+            - origin
+    }
 }
 
 impl FromHCY for InfinitePlane {
     fn from_hcy(_member: Option<&str>, lines: Vec<String>) -> Result<Self, Box<dyn Error>> {
         let mut origin = None;
         let mut normal = None;
+        let mut extent = None;
 
         for line in lines.into_iter() {
             let (key, value) = line
@@ -58,13 +102,40 @@ impl FromHCY for InfinitePlane {
             match key.trim() {
                 "origin" => origin = Some(parse_into(value)?),
                 "normal" => normal = Some(parse_into(value)?),
+                "extent" => extent = Some(parse_into(value)?),
                 _ => {}
             }
         }
 
-        Ok(InfinitePlane::new(
+        let mut plane = InfinitePlane::new(
             origin.ok_or("missing required key `origin`")?,
             normal.ok_or("missing required key `normal`")?,
-        ))
+        );
+        plane.extent = extent;
+        Ok(plane)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn bounded_planes_sample_as_lights() {
+        let mut plane = InfinitePlane::new(vec3::splat(0.0), vec3::unit_y());
+        assert!(!plane.can_sample());
+
+        plane.extent = Some(vec2::new(1.0, 1.0));
+        assert!(plane.can_sample());
+        let origin = vec3::new(0.0, 2.0, 0.0);
+        assert!((plane.pdf_value(&origin, &-vec3::unit_y()) - 1.0).abs() < 1e-4);
+        assert_eq!(plane.pdf_value(&origin, &vec3::new(2.0, -1.0, 0.0)), 0.0);
+
+        for _ in 0..16 {
+            let dir = plane.random(&origin);
+            assert!(plane
+                .intersect(&Ray::new(origin, dir), 0.001, 2.0)
+                .is_some());
+        }
     }
 }
@@ -5,23 +5,40 @@ use crate::{
     accel::{Accel, Accelerator},
     load_obj,
     loader::{parse_into, FromHCY},
-    maths::{vec3, Ray},
+    maths::{vec2, vec3, Distribution1D, Ray},
 };
 
 #[derive(Clone, Debug)]
 pub struct Mesh {
     tris: Accel<Triangle>,
     normals: Option<Vec<[vec3; 3]>>,
+    texcoords: Option<Vec<[vec2; 3]>>,
+    // picks triangles in proportion to their area when the mesh is sampled as a light
+    areas: Distribution1D,
 }
 
 impl Mesh {
     pub fn new(positions: &[vec3], indices: &Vec<u32>, vnormals: &[vec3]) -> Self {
+        Self::new_with_texcoords(positions, indices, vnormals, &[])
+    }
+
+    pub fn new_with_texcoords(
+        positions: &[vec3],
+        indices: &Vec<u32>,
+        vnormals: &[vec3],
+        vtexcoords: &[vec2],
+    ) -> Self {
         let mut tris = vec![Triangle::default(); indices.len() / 3];
         let mut normals = if vnormals.is_empty() {
             None
         } else {
             Some(Vec::with_capacity(indices.len()))
         };
+        let mut texcoords = if vtexcoords.is_empty() {
+            None
+        } else {
+            Some(Vec::with_capacity(indices.len()))
+        };
         for i in 0..indices.len() / 3 {
             tris[i] = Triangle::new([
                 positions[indices[i * 3] as usize],
@@ -36,12 +53,28 @@ impl Mesh {
                     vnormals[indices[i * 3 + 2] as usize],
                 ]);
             }
+
+            if let Some(texcoords) = &mut texcoords {
+                texcoords.push([
+                    vtexcoords[indices[i * 3] as usize],
+                    vtexcoords[indices[i * 3 + 1] as usize],
+                    vtexcoords[indices[i * 3 + 2] as usize],
+                ]);
+            }
         }
+        let tris = Accel::new(tris);
+        let areas = Distribution1D::new(tris.hittables.iter().map(Triangle::area).collect());
         Mesh {
-            tris: Accel::new(tris),
+            tris,
             normals,
+            texcoords,
+            areas,
         }
     }
+
+    pub fn triangles(&self) -> &[Triangle] {
+        &self.tris.hittables
+    }
 }
 
 impl Hittable for Mesh {
@@ -54,31 +87,20 @@ impl Hittable for Mesh {
     fn get_bounce_info(&self, ray: &Ray, intersection: Intersection) -> BounceInfo {
         let idx = intersection.i as usize;
         let mut bounce_info = BounceInfo::new(ray, intersection.t, vec3::default());
+        let bary = self.tris.hittables[idx].barycentric(&bounce_info.p);
         bounce_info.set_normal(
             ray,
             if let Some(normals) = &self.normals {
-                let a = self.tris.hittables[idx].vertices[0];
-                let b = self.tris.hittables[idx].vertices[1];
-                let c = self.tris.hittables[idx].vertices[2];
-
-                let v0 = b - a;
-                let v1 = c - a;
-                let v2 = bounce_info.p - a;
-                let d00 = v0.dot(v0);
-                let d01 = v0.dot(v1);
-                let d11 = v1.dot(v1);
-                let d20 = v2.dot(v0);
-                let d21 = v2.dot(v1);
-                let denom = d00 * d11 - d01 * d01;
-                let v = (d11 * d20 - d01 * d21) / denom;
-                let w = (d00 * d21 - d01 * d20) / denom;
-                let u = 1.0 - v - w;
-
-                u * normals[idx][0] + v * normals[idx][1] + w * normals[idx][2]
+                bary.x * normals[idx][0] + bary.y * normals[idx][1] + bary.z * normals[idx][2]
             } else {
                 self.tris.hittables[idx].normal()
             },
         );
+        bounce_info.uv = if let Some(texcoords) = &self.texcoords {
+            bary.x * texcoords[idx][0] + bary.y * texcoords[idx][1] + bary.z * texcoords[idx][2]
+        } else {
+            vec2::new(bary.y, bary.z)
+        };
 
         bounce_info
     }
@@ -95,6 +117,35 @@ impl Hittable for Mesh {
 
         AABB::new(min - vec3::splat(0.001), max + vec3::splat(0.001))
     }
+
+    fn can_sample(&self) -> bool {
+        self.areas.integral > 0.0
+    }
+
+    fn pdf_value(&self, origin: &vec3, dir: &vec3) -> f32 {
+        let area = self.areas.integral * self.areas.len() as f32;
+        let ray = Ray::new(*origin, *dir);
+        let mut t_min = 0.001;
+        let mut pdf = 0.0;
+        // every crossing of the surface could have been the sampled point
+        while let Some((intersection, idx)) =
+            self.tris.intersect_with_index(&ray, t_min, f32::INFINITY)
+        {
+            let dist_sq = intersection.t * intersection.t * dir.mag_sq();
+            let cosine = (dir.dot(self.tris.hittables[idx].normal()) / dir.mag()).abs();
+            if cosine > 0.0 {
+                pdf += dist_sq / (cosine * area);
+            }
+            t_min = intersection.t + 0.001;
+        }
+
+        pdf
+    }
+
+    fn random(&self, origin: &vec3) -> vec3 {
+        let (_, _, idx) = self.areas.sample(rand::random());
+        self.tris.hittables[idx].random_point() - origin
+    }
 }
 
 impl FromHCY for Mesh {
@@ -120,6 +171,11 @@ impl FromHCY for Mesh {
             meshes.len(),
         ))?;
 
-        Ok(Self::new(&mesh.vertices, &mesh.indices, &mesh.normals))
+        Ok(Self::new_with_texcoords(
+            &mesh.vertices,
+            &mesh.indices,
+            &mesh.normals,
+            &mesh.texcoords,
+        ))
     }
 }
@@ -1,8 +1,13 @@
-use std::{error::Error, fmt::Debug};
+use std::{error::Error, f32::consts::PI, fmt::Debug};
 
 use enum_dispatch::enum_dispatch;
 
-use crate::{loader::FromHCY, maths::vec3, maths::Ray};
+use crate::{
+    color::Color,
+    loader::FromHCY,
+    maths::Ray,
+    maths::{vec2, vec3},
+};
 
 #[derive(Debug)]
 pub struct BounceInfo {
@@ -10,6 +15,9 @@ pub struct BounceInfo {
     pub p: vec3,
     pub normal: vec3,
     pub front_face: bool,
+    pub uv: vec2,
+    // a color carried by the surface itself, like a painted sdf
+    pub color: Option<Color>,
 }
 
 impl BounceInfo {
@@ -21,6 +29,8 @@ impl BounceInfo {
             p: src_ray.at(t),
             normal: if front_face { normal } else { -normal },
             front_face,
+            uv: vec2::default(),
+            color: None,
         }
     }
 
@@ -34,7 +44,7 @@ impl BounceInfo {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub struct Intersection {
     pub t: f32,
     pub i: u32,
@@ -49,13 +59,58 @@ pub trait Hittable: Send + Sync + Clone + Debug {
 
     fn make_bounding_box(&self) -> AABB;
 
+    // whether `pdf_value` and `random` sample the surface, anything that overrides this
+    // has to override them too. callers check it before sampling, the scene refuses emissive
+    // objects that can't be sampled.
+    fn can_sample(&self) -> bool {
+        false
+    }
+
     fn pdf_value(&self, origin: &vec3, dir: &vec3) -> f32 {
-        panic!("oof you need to implement this :<");
+        1.0 / (4.0 * PI)
     }
 
     fn random(&self, origin: &vec3) -> vec3 {
-        panic!("haha you need to implement this :<");
+        vec3::random_in_unit_sphere().normalized()
+    }
+
+    // whether the shape has an inside, which `spans` then lists the crossings of along the
+    // whole line of the ray, not just its positive half. csg needs both.
+    fn is_closed(&self) -> bool {
+        false
+    }
+
+    fn spans(&self, ray: &Ray) -> Vec<Span> {
+        vec![]
+    }
+}
+
+// solid angle density of picking `dir` by choosing a point uniformly over the surface area of
+// `hittable`, a ray can cross curved surfaces more than once so every crossing adds to it.
+pub(crate) fn area_pdf_value<H: Hittable>(
+    hittable: &H,
+    area: f32,
+    origin: &vec3,
+    dir: &vec3,
+) -> f32 {
+    let ray = Ray::new(*origin, *dir);
+    let mut t_min = 0.001;
+    let mut pdf = 0.0;
+    // nothing here crosses a line more than four times
+    for _ in 0..4 {
+        let Some(intersection) = hittable.intersect(&ray, t_min, f32::INFINITY) else {
+            break;
+        };
+        let bounce = hittable.get_bounce_info(&ray, intersection);
+        let dist_sq = intersection.t * intersection.t * dir.mag_sq();
+        let cosine = (dir.dot(bounce.normal) / dir.mag()).abs();
+        if cosine > 0.0 {
+            pdf += dist_sq / (cosine * area);
+        }
+        t_min = intersection.t + 0.001;
     }
+
+    pdf
 }
 
 mod sphere;
@@ -79,6 +134,27 @@ pub use aabb::*;
 mod sdf;
 pub use sdf::*;
 
+mod cylinder;
+pub use cylinder::*;
+
+mod cone;
+pub use cone::*;
+
+mod disk;
+pub use disk::*;
+
+mod capsule;
+pub use capsule::*;
+
+mod torus;
+pub use torus::*;
+
+mod csg;
+pub use csg::*;
+
+mod heightfield;
+pub use heightfield::*;
+
 #[enum_dispatch(Hittable)]
 #[derive(Clone, Debug)]
 pub enum HittableObject {
@@ -89,6 +165,13 @@ pub enum HittableObject {
     Mesh,
     AABB,
     HittableSDF,
+    Cylinder,
+    Cone,
+    Disk,
+    Capsule,
+    Torus,
+    CSG,
+    Heightfield,
 }
 
 impl FromHCY for HittableObject {
@@ -102,6 +185,20 @@ impl FromHCY for HittableObject {
             )?)),
             "mesh" => Ok(HittableObject::Mesh(Mesh::from_hcy(None, lines)?)),
             "sphere" => Ok(HittableObject::Sphere(Sphere::from_hcy(None, lines)?)),
+            "cylinder" => Ok(HittableObject::Cylinder(Cylinder::from_hcy(None, lines)?)),
+            "cone" => Ok(HittableObject::Cone(Cone::from_hcy(None, lines)?)),
+            "disk" => Ok(HittableObject::Disk(Disk::from_hcy(None, lines)?)),
+            "capsule" => Ok(HittableObject::Capsule(Capsule::from_hcy(None, lines)?)),
+            "torus" => Ok(HittableObject::Torus(Torus::from_hcy(None, lines)?)),
+            "heightfield" => Ok(HittableObject::Heightfield(Heightfield::from_hcy(
+                None, lines,
+            )?)),
+            "union" | "intersection" | "difference" => {
+                Ok(HittableObject::CSG(CSG::from_hcy(Some(member), lines)?))
+            }
+            "sdf" => Ok(HittableObject::HittableSDF(HittableSDF::from_hcy(
+                None, lines,
+            )?)),
             _ => Err(format!("unknown primitive type {member}"))?,
         }
     }
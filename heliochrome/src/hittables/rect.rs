@@ -3,7 +3,7 @@ use std::error::Error;
 use crate::{
     hittables::{BounceInfo, Hittable, Intersection, AABB},
     loader::{parse_into, FromHCY},
-    maths::{mat3, vec3, Ray},
+    maths::{mat3, vec2, vec3, Ray},
 };
 
 #[derive(Clone, Debug)]
@@ -38,7 +38,12 @@ impl Hittable for Rect {
     }
 
     fn get_bounce_info(&self, ray: &Ray, intersection: Intersection) -> BounceInfo {
-        BounceInfo::new(ray, intersection.t, self.normal)
+        let mut bounce_info = BounceInfo::new(ray, intersection.t, self.normal);
+        let ol =
+            mat3::new([self.s1, self.s2, self.normal]).inverse() * (bounce_info.p - self.origin);
+        bounce_info.uv = vec2::new(ol.x, ol.y);
+
+        bounce_info
     }
 
     fn make_bounding_box(&self) -> AABB {
@@ -58,6 +63,10 @@ impl Hittable for Rect {
         AABB::new(min, max)
     }
 
+    fn can_sample(&self) -> bool {
+        true
+    }
+
     fn pdf_value(&self, origin: &vec3, dir: &vec3) -> f32 {
         if let Some(bounce) = self.intersect(&Ray::new(*origin, *dir), 0.001, f32::INFINITY) {
             let area = self.s1.mag() * self.s2.mag();
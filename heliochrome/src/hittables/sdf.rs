@@ -1,37 +1,82 @@
-use std::sync::Arc;
+use std::{error::Error, sync::Arc};
 
 use super::{BounceInfo, Hittable, Intersection, AABB};
-use crate::{maths::Ray, sdf::SDF};
-
-const MIN_DIST: f32 = 0.000001;
-const MAX_MARCHES: u16 = 500;
-const MARCH_T_MAX: f32 = 10000.0;
+use crate::{
+    loader::{collect_nested_item, parse_into, FromHCY},
+    maths::Ray,
+    sdf::SDF,
+};
 
 #[derive(Clone, Debug)]
 pub struct HittableSDF {
     sdf: Arc<dyn SDF>,
+    bounds: AABB,
+
+    pub max_marches: u32,
+    // a hit is anything closer than the larger of these, the relative one grows with the
+    // distance along the ray like the footprint of a pixel does
+    pub epsilon: f32,
+    pub relative_epsilon: f32,
+    pub max_distance: f32,
+    // over relaxation of each step, 1 is plain sphere tracing
+    pub relaxation: f32,
 }
 
 impl HittableSDF {
     pub fn new(de: impl SDF + 'static) -> Self {
-        Self { sdf: Arc::new(de) }
+        Self {
+            bounds: de.make_bounding_box(),
+            sdf: Arc::new(de),
+            max_marches: 500,
+            epsilon: 0.00001,
+            relative_epsilon: 0.0001,
+            max_distance: 10000.0,
+            relaxation: 1.2,
+        }
     }
 }
 
 impl Hittable for HittableSDF {
     fn intersect(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<Intersection> {
-        let mut t = t_min;
-        let mut p = ray.at(t);
+        // only march the part of the ray inside the bounding box
+        let dir_len = ray.direction.mag();
+        let (mut t, t_end) =
+            self.bounds
+                .clip(ray, t_min, t_max.min(self.max_distance / dir_len))?;
+
+        // rays starting inside, like refracted ones, march to the surface from within
+        let sign = self.sdf.dist(ray.at(t)).signum();
+
+        // enhanced sphere tracing (Keinert et al. 2014), steps are stretched by the relaxation
+        // and taken back whenever the unbounding spheres of two steps stop overlapping.
+        let mut omega = self.relaxation;
+        let mut prev_radius = 0.0;
+        let mut step = 0.0;
+        for _ in 0..self.max_marches {
+            let signed_radius = sign * self.sdf.dist(ray.at(t)) / dir_len;
+            let radius = signed_radius.abs();
+
+            if omega > 1.0 && radius + prev_radius < step {
+                t -= step - prev_radius;
+                step = prev_radius;
+                omega = 1.0;
+                continue;
+            }
+
+            if radius * dir_len < self.epsilon.max(self.relative_epsilon * t * dir_len) {
+                return (t >= t_min).then_some(Intersection { t, i: 0 });
+            }
 
-        for _ in 0..MAX_MARCHES {
-            let d = self.sdf.dist(p);
-            t += d;
-            if t > t_max || t > MARCH_T_MAX || t < t_min {
+            // nothing is closer than the unrelaxed step, the relaxed one may overshoot the box
+            if t + signed_radius > t_end + self.epsilon / dir_len {
                 break;
             }
-            p = ray.at(t);
-            if d.abs() < MIN_DIST {
-                return Some(Intersection { t, i: 0 });
+
+            step = signed_radius * omega;
+            prev_radius = radius;
+            t += step;
+            if t < t_min {
+                break;
             }
         }
 
@@ -39,14 +84,77 @@ impl Hittable for HittableSDF {
     }
 
     fn get_bounce_info(&self, ray: &Ray, intersection: Intersection) -> BounceInfo {
-        BounceInfo::new(
-            ray,
-            intersection.t,
-            self.sdf.normal_at(&ray.at(intersection.t)),
-        )
+        let p = ray.at(intersection.t);
+        let mut bounce_info = BounceInfo::new(ray, intersection.t, self.sdf.normal_at(&p));
+        bounce_info.color = self.sdf.color(p);
+        bounce_info
     }
 
     fn make_bounding_box(&self) -> AABB {
-        self.sdf.make_bounding_box()
+        self.bounds
+    }
+}
+
+impl FromHCY for HittableSDF {
+    fn from_hcy(_member: Option<&str>, lines: Vec<String>) -> Result<Self, Box<dyn Error>> {
+        let mut sdf = None;
+        let mut max_marches = None;
+        let mut epsilon = None;
+        let mut relative_epsilon = None;
+        let mut max_distance = None;
+        let mut relaxation = None;
+
+        let mut line_iter = lines.iter();
+        while let Some(line) = line_iter.next() {
+            let (key, value) = line
+                .split_once(':')
+                .ok_or("invalid key value pair syntax")?;
+            match key.trim() {
+                "shape" => {
+                    sdf = Some(
+                        Box::<dyn SDF>::from_hcy(
+                            Some(value),
+                            collect_nested_item(line, &mut line_iter),
+                        )
+                        .map_err(|err| format!("could not parse shape key: {err}"))?,
+                    );
+                }
+                "max marches" => max_marches = Some(parse_into(value)?),
+                "epsilon" => epsilon = Some(parse_into(value)?),
+                "relative epsilon" => relative_epsilon = Some(parse_into(value)?),
+                "max distance" => max_distance = Some(parse_into(value)?),
+                "relaxation" => relaxation = Some(parse_into(value)?),
+                _ => {}
+            }
+        }
+
+        let mut hittable = HittableSDF::new(sdf.ok_or("missing required key `shape`")?);
+        hittable.max_marches = max_marches.unwrap_or(hittable.max_marches);
+        hittable.epsilon = epsilon.unwrap_or(hittable.epsilon);
+        hittable.relative_epsilon = relative_epsilon.unwrap_or(hittable.relative_epsilon);
+        hittable.max_distance = max_distance.unwrap_or(hittable.max_distance);
+        hittable.relaxation = relaxation.unwrap_or(hittable.relaxation);
+        Ok(hittable)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{maths::vec3, sdf::Sphere};
+
+    #[test]
+    fn marches_to_the_surface_from_both_sides() {
+        let sphere = HittableSDF::new(Sphere::new(1.0, vec3::splat(0.0)));
+        let outside = Ray::new(vec3::new(0.0, 0.0, -5.0), vec3::new(0.0, 0.0, 2.0));
+        let t = sphere.intersect(&outside, 0.001, f32::INFINITY).unwrap().t;
+        assert!((t - 2.0).abs() < 0.001);
+
+        let inside = Ray::new(vec3::splat(0.0), vec3::new(0.0, 2.0, 0.0));
+        let t = sphere.intersect(&inside, 0.001, f32::INFINITY).unwrap().t;
+        assert!((t - 0.5).abs() < 0.001);
+
+        let miss = Ray::new(vec3::new(0.0, 1.1, -5.0), vec3::unit_z());
+        assert!(sphere.intersect(&miss, 0.001, f32::INFINITY).is_none());
     }
 }
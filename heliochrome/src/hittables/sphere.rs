@@ -1,6 +1,6 @@
 use std::error::Error;
 
-use super::{Intersection, AABB};
+use super::{Intersection, Span, AABB};
 use crate::{
     hittables::{BounceInfo, Hittable},
     loader::{parse_into, FromHCY},
@@ -56,11 +56,14 @@ impl Hittable for Sphere {
     }
 
     fn get_bounce_info(&self, ray: &Ray, intersection: Intersection) -> BounceInfo {
-        BounceInfo::new(
-            ray,
-            intersection.t,
-            (ray.at(intersection.t) - self.center) / self.radius,
-        )
+        let outward = (ray.at(intersection.t) - self.center) / self.radius;
+        let mut bounce_info = BounceInfo::new(ray, intersection.t, outward);
+        bounce_info.uv = vec2::new(
+            0.5 + (-outward.z).atan2(outward.x) / std::f32::consts::TAU,
+            (-outward.y).acos() / std::f32::consts::PI,
+        );
+
+        bounce_info
     }
 
     fn make_bounding_box(&self) -> AABB {
@@ -70,6 +73,38 @@ impl Hittable for Sphere {
         )
     }
 
+    fn is_closed(&self) -> bool {
+        true
+    }
+
+    fn spans(&self, ray: &Ray) -> Vec<Span> {
+        let oc = ray.origin - self.center;
+        let a = ray.direction.mag_sq();
+        let half_b = oc.dot(ray.direction);
+        let c = oc.mag_sq() - self.radius * self.radius;
+
+        let discriminant = half_b * half_b - a * c;
+        if discriminant <= 0.0 {
+            return vec![];
+        }
+        let sqrt_d = discriminant.sqrt();
+
+        vec![Span {
+            enter: Intersection {
+                t: (-half_b - sqrt_d) / a,
+                i: 0,
+            },
+            exit: Intersection {
+                t: (-half_b + sqrt_d) / a,
+                i: 0,
+            },
+        }]
+    }
+
+    fn can_sample(&self) -> bool {
+        true
+    }
+
     fn pdf_value(&self, origin: &vec3, dir: &vec3) -> f32 {
         if self
             .intersect(&Ray::new(*origin, *dir), 0.001, f32::INFINITY)
@@ -0,0 +1,149 @@
+use std::error::Error;
+use std::f32::consts::TAU;
+
+use super::{area_pdf_value, BounceInfo, Hittable, Intersection, AABB};
+use crate::{
+    loader::{parse_into, FromHCY},
+    maths::{solve_quartic, vec2, vec3, Ray, ONB},
+};
+
+// an analytic torus around `axis`, see sdf::Torus for the ray marched one
+#[derive(Clone, Debug)]
+pub struct Torus {
+    center: vec3,
+    major_radius: f32,
+    minor_radius: f32,
+    onb: ONB,
+}
+
+impl Torus {
+    pub fn new(center: vec3, axis: vec3, major_radius: f32, minor_radius: f32) -> Self {
+        Self {
+            center,
+            major_radius,
+            minor_radius,
+            onb: ONB::new_from_w(axis.normalized()),
+        }
+    }
+
+    fn area(&self) -> f32 {
+        TAU * TAU * self.major_radius * self.minor_radius
+    }
+}
+
+impl Hittable for Torus {
+    fn intersect(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<Intersection> {
+        // everything in f64 with a unit direction, the quartic is badly conditioned otherwise
+        let dir_len = ray.direction.mag();
+        let o = self.onb.to_local(&(ray.origin - self.center));
+        let d = self.onb.to_local(&ray.direction) / dir_len;
+        let (ox, oy, oz) = (o.x as f64, o.y as f64, o.z as f64);
+        let (dx, dy, dz) = (d.x as f64, d.y as f64, d.z as f64);
+        let big_r2 = (self.major_radius as f64).powi(2);
+        let small_r2 = (self.minor_radius as f64).powi(2);
+
+        // (|p|^2 + R^2 - r^2)^2 = 4 R^2 (x^2 + y^2) along p = o + t d
+        let od = ox * dx + oy * dy + oz * dz;
+        let k = ox * ox + oy * oy + oz * oz + big_r2 - small_r2;
+        let roots = solve_quartic(
+            4.0 * od,
+            4.0 * od * od + 2.0 * k - 4.0 * big_r2 * (dx * dx + dy * dy),
+            4.0 * k * od - 8.0 * big_r2 * (ox * dx + oy * dy),
+            k * k - 4.0 * big_r2 * (ox * ox + oy * oy),
+        );
+
+        roots
+            .into_iter()
+            .map(|t| t as f32 / dir_len)
+            .filter(|t| t_min <= *t && *t <= t_max)
+            .min_by(|a, b| a.total_cmp(b))
+            .map(|t| Intersection { t, i: 0 })
+    }
+
+    fn get_bounce_info(&self, ray: &Ray, intersection: Intersection) -> BounceInfo {
+        let p = self.onb.to_local(&(ray.at(intersection.t) - self.center));
+        let ring = vec3::new(p.x, p.y, 0.0).normalized();
+        let local_normal = (p - ring * self.major_radius).normalized();
+
+        let mut bounce_info = BounceInfo::new(ray, intersection.t, self.onb.local(&local_normal));
+        bounce_info.uv = vec2::new(
+            0.5 + p.y.atan2(p.x) / TAU,
+            0.5 + p
+                .z
+                .atan2((p.x * p.x + p.y * p.y).sqrt() - self.major_radius)
+                / TAU,
+        );
+
+        bounce_info
+    }
+
+    fn make_bounding_box(&self) -> AABB {
+        let axis = self.onb.w;
+        // the ring is a circle of the major radius, thickened by the minor radius everywhere
+        let extent = (vec3::splat(1.0) - axis * axis)
+            .max(&vec3::splat(0.0))
+            .sqrt()
+            * self.major_radius
+            + vec3::splat(self.minor_radius);
+        AABB::new(self.center - extent, self.center + extent)
+    }
+
+    fn can_sample(&self) -> bool {
+        true
+    }
+
+    fn pdf_value(&self, origin: &vec3, dir: &vec3) -> f32 {
+        area_pdf_value(self, self.area(), origin, dir)
+    }
+
+    fn random(&self, origin: &vec3) -> vec3 {
+        // the outside of the tube has more area than the inside, so reject angles in
+        // proportion to their distance from the axis
+        let theta = loop {
+            let theta = TAU * rand::random::<f32>();
+            let weight = (self.major_radius + self.minor_radius * theta.cos())
+                / (self.major_radius + self.minor_radius);
+            if rand::random::<f32>() <= weight {
+                break theta;
+            }
+        };
+        let phi = TAU * rand::random::<f32>();
+
+        let ring = self.major_radius + self.minor_radius * theta.cos();
+        let local = vec3::new(
+            ring * phi.cos(),
+            ring * phi.sin(),
+            self.minor_radius * theta.sin(),
+        );
+        self.center + self.onb.local(&local) - origin
+    }
+}
+
+impl FromHCY for Torus {
+    fn from_hcy(_member: Option<&str>, lines: Vec<String>) -> Result<Self, Box<dyn Error>> {
+        let mut center = None;
+        let mut axis = vec3::unit_y();
+        let mut major_radius = None;
+        let mut minor_radius = None;
+
+        for line in lines.into_iter() {
+            let (key, value) = line
+                .split_once(':')
+                .ok_or("invalid key value pair syntax")?;
+            match key.trim() {
+                "center" => center = Some(parse_into(value)?),
+                "axis" => axis = parse_into(value)?,
+                "major radius" => major_radius = Some(parse_into(value)?),
+                "minor radius" => minor_radius = Some(parse_into(value)?),
+                _ => {}
+            }
+        }
+
+        Ok(Torus::new(
+            center.ok_or("missing required key `center`")?,
+            axis,
+            major_radius.ok_or("missing required key `major radius`")?,
+            minor_radius.ok_or("missing required key `minor radius`")?,
+        ))
+    }
+}
@@ -1,5 +1,5 @@
-use super::{BounceInfo, Hittable, Intersection, AABB};
-use crate::maths::{vec3, Ray};
+use super::{area_pdf_value, BounceInfo, Hittable, Intersection, AABB};
+use crate::maths::{vec2, vec3, Ray};
 
 #[derive(Clone, Default, Debug)]
 pub struct Triangle {
@@ -16,6 +16,82 @@ impl Triangle {
         let edge2 = self.vertices[2] - self.vertices[0];
         edge2.cross(edge1).normalize()
     }
+
+    pub fn barycentric(&self, p: &vec3) -> vec3 {
+        let v0 = self.vertices[1] - self.vertices[0];
+        let v1 = self.vertices[2] - self.vertices[0];
+        let v2 = p - self.vertices[0];
+        let d00 = v0.dot(v0);
+        let d01 = v0.dot(v1);
+        let d11 = v1.dot(v1);
+        let d20 = v2.dot(v0);
+        let d21 = v2.dot(v1);
+        let denom = d00 * d11 - d01 * d01;
+        let v = (d11 * d20 - d01 * d21) / denom;
+        let w = (d00 * d21 - d01 * d20) / denom;
+
+        vec3::new(1.0 - v - w, v, w)
+    }
+
+    pub fn area(&self) -> f32 {
+        let edge1 = self.vertices[1] - self.vertices[0];
+        let edge2 = self.vertices[2] - self.vertices[0];
+        0.5 * edge1.cross(edge2).mag()
+    }
+
+    // the point on the triangle closest to p, by the voronoi region of p (Ericson 5.1.5)
+    pub fn closest_point(&self, p: &vec3) -> vec3 {
+        let [a, b, c] = self.vertices;
+        let ab = b - a;
+        let ac = c - a;
+        let ap = p - a;
+        let d1 = ab.dot(ap);
+        let d2 = ac.dot(ap);
+        if d1 <= 0.0 && d2 <= 0.0 {
+            return a;
+        }
+
+        let bp = p - b;
+        let d3 = ab.dot(bp);
+        let d4 = ac.dot(bp);
+        if d3 >= 0.0 && d4 <= d3 {
+            return b;
+        }
+
+        let vc = d1 * d4 - d3 * d2;
+        if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+            return a + ab * (d1 / (d1 - d3));
+        }
+
+        let cp = p - c;
+        let d5 = ab.dot(cp);
+        let d6 = ac.dot(cp);
+        if d6 >= 0.0 && d5 <= d6 {
+            return c;
+        }
+
+        let vb = d5 * d2 - d1 * d6;
+        if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+            return a + ac * (d2 / (d2 - d6));
+        }
+
+        let va = d3 * d6 - d5 * d4;
+        if va <= 0.0 && d4 - d3 >= 0.0 && d5 - d6 >= 0.0 {
+            return b + (c - b) * ((d4 - d3) / ((d4 - d3) + (d5 - d6)));
+        }
+
+        let denom = 1.0 / (va + vb + vc);
+        a + ab * (vb * denom) + ac * (vc * denom)
+    }
+
+    // a uniformly distributed point on the triangle
+    pub fn random_point(&self) -> vec3 {
+        let s = rand::random::<f32>().sqrt();
+        let t = rand::random::<f32>();
+        self.vertices[0] * (1.0 - s)
+            + self.vertices[1] * (s * (1.0 - t))
+            + self.vertices[2] * (s * t)
+    }
 }
 
 impl Hittable for Triangle {
@@ -48,7 +124,11 @@ impl Hittable for Triangle {
     }
 
     fn get_bounce_info(&self, ray: &Ray, intersection: Intersection) -> BounceInfo {
-        BounceInfo::new(ray, intersection.t, self.normal())
+        let mut bounce_info = BounceInfo::new(ray, intersection.t, self.normal());
+        let bary = self.barycentric(&bounce_info.p);
+        bounce_info.uv = vec2::new(bary.y, bary.z);
+
+        bounce_info
     }
 
     fn make_bounding_box(&self) -> AABB {
@@ -61,4 +141,16 @@ impl Hittable for Triangle {
 
         AABB::new(min - vec3::splat(0.0001), max + vec3::splat(0.0001))
     }
+
+    fn can_sample(&self) -> bool {
+        true
+    }
+
+    fn pdf_value(&self, origin: &vec3, dir: &vec3) -> f32 {
+        area_pdf_value(self, self.area(), origin, dir)
+    }
+
+    fn random(&self, origin: &vec3) -> vec3 {
+        self.random_point() - origin
+    }
 }
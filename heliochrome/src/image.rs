@@ -20,18 +20,48 @@ impl Image {
         }
     }
 
-    pub fn load_from_hdri(path: &Path) -> Result<Image, Box<dyn Error>> {
+    // decodes an rgb image bottom row first, passing every pixel through `map`
+    fn load_rgb(path: &Path, map: impl Fn(Color) -> Color) -> Result<Image, Box<dyn Error>> {
         let img = image::open(path)?.flipv().into_rgb32f();
 
         let (width, height) = img.dimensions();
 
-        return Ok(Image {
+        Ok(Image {
+            size: vec2::new(width as f32, height as f32),
+            buffer: img
+                .enumerate_pixels()
+                .map(|p| map(Color::new(p.2[0], p.2[1], p.2[2])))
+                .collect::<Vec<_>>(),
+        })
+    }
+
+    // loads a linear high dynamic range image such as .hdr or .exr, keeping values above one
+    pub fn load_from_hdri(path: &Path) -> Result<Image, Box<dyn Error>> {
+        Self::load_rgb(path, |c| c.max(&Color::splat(0.0)))
+    }
+
+    // textures are stored gamma encoded, so undo that for rendering
+    pub fn load_texture(path: &Path) -> Result<Image, Box<dyn Error>> {
+        Self::load_rgb(path, |c| c.powf(2.2))
+    }
+
+    // loads the values as they are stored, for data like heightmaps that isn't gamma encoded
+    pub fn load_linear(path: &Path) -> Result<Image, Box<dyn Error>> {
+        Self::load_rgb(path, |c| c)
+    }
+
+    pub fn load_alpha(path: &Path) -> Result<Image, Box<dyn Error>> {
+        let img = image::open(path)?.flipv().into_rgba32f();
+
+        let (width, height) = img.dimensions();
+
+        Ok(Image {
             size: vec2::new(width as f32, height as f32),
             buffer: img
                 .enumerate_pixels()
-                .map(|p| Color::new(p.2[0].min(1.0), p.2[1].min(1.0), p.2[2].min(1.0)))
+                .map(|p| Color::splat(p.2[3]))
                 .collect::<Vec<_>>(),
-        });
+        })
     }
 
     pub fn sample_uv(&self, uv: &vec2) -> Color {
@@ -6,6 +6,8 @@
 // lib but not and maths
 #![allow(dead_code)]
 #![allow(non_camel_case_types)]
+// `Option::is_none_or` is newer than the pinned nightly
+#![allow(clippy::unnecessary_map_or)]
 
 pub mod load_obj;
 
@@ -13,8 +15,10 @@ pub mod accel;
 pub mod camera;
 pub mod color;
 pub mod context;
+pub mod environment;
 pub mod hittables;
 pub mod image;
+pub mod light;
 pub mod loader;
 pub mod materials;
 pub mod maths;
@@ -22,6 +26,8 @@ pub mod object;
 pub mod pdf;
 pub mod scene;
 pub mod sdf;
+pub mod sky;
+pub mod texture;
 pub mod tonemap;
 pub mod transform;
 pub mod util;
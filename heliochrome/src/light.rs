@@ -0,0 +1,138 @@
+use std::error::Error;
+
+use crate::{
+    color::Color,
+    loader::{parse_into, FromHCY},
+    materials::SpotFalloff,
+    maths::{vec3, ONB},
+};
+
+// lights that are not part of the geometry, so rays can never hit them and they are only
+// reached by shadow rays from the integrator.
+#[derive(Clone, Debug)]
+pub enum Light {
+    Point {
+        position: vec3,
+        color: Color,
+    },
+    Spot {
+        position: vec3,
+        direction: vec3,
+        falloff: SpotFalloff,
+        color: Color,
+    },
+    Directional {
+        // the direction the light travels in
+        direction: vec3,
+        // cosine of half the angular diameter
+        cos_max: f32,
+        // irradiance on a surface facing the light
+        color: Color,
+    },
+}
+
+pub struct LightSample {
+    pub dir: vec3,
+    pub dist: f32,
+    // incoming radiance already divided by the pdf of picking `dir`
+    pub color: Color,
+}
+
+impl Light {
+    pub fn sample(&self, p: &vec3) -> LightSample {
+        match self {
+            Light::Point { position, color } => {
+                let to_light = position - p;
+                let dist_sq = to_light.mag_sq();
+                LightSample {
+                    dir: to_light / dist_sq.sqrt(),
+                    dist: dist_sq.sqrt(),
+                    color: color / dist_sq,
+                }
+            }
+            Light::Spot {
+                position,
+                direction,
+                falloff,
+                color,
+            } => {
+                let to_light = position - p;
+                let dist_sq = to_light.mag_sq();
+                let dir = to_light / dist_sq.sqrt();
+                LightSample {
+                    dir,
+                    dist: dist_sq.sqrt(),
+                    color: color * falloff.falloff((-dir).dot(*direction)) / dist_sq,
+                }
+            }
+            Light::Directional {
+                direction,
+                cos_max,
+                color,
+            } => {
+                // uniformly sampling the cone, the solid angle cancels with the pdf
+                let z = 1.0 - rand::random::<f32>() * (1.0 - cos_max);
+                let phi = std::f32::consts::TAU * rand::random::<f32>();
+                let r = (1.0 - z * z).max(0.0).sqrt();
+                let onb = ONB::new_from_w(-direction);
+                LightSample {
+                    dir: onb.local(&vec3::new(phi.cos() * r, phi.sin() * r, z)),
+                    dist: f32::INFINITY,
+                    color: *color,
+                }
+            }
+        }
+    }
+}
+
+impl FromHCY for Light {
+    fn from_hcy(member: Option<&str>, lines: Vec<String>) -> Result<Self, Box<dyn Error>> {
+        let member = member.ok_or("invalid syntax missing member specifier")?;
+
+        let mut position = None;
+        let mut direction = None;
+        let mut color = None;
+        let mut intensity = None;
+        let mut angle = None;
+        let mut blend = 0.0;
+        let mut angular_diameter = 0.0f32;
+
+        for line in lines.into_iter() {
+            let (key, value) = line
+                .split_once(':')
+                .ok_or("invalid key value pair syntax")?;
+            match key.trim() {
+                "position" => position = Some(parse_into(value)?),
+                "direction" => direction = Some(parse_into::<vec3>(value)?.normalized()),
+                "color" => color = Some(parse_into::<Color>(value)?),
+                "intensity" => intensity = Some(parse_into::<f32>(value)?),
+                "angle" => angle = Some(parse_into(value)?),
+                "blend" => blend = parse_into(value)?,
+                "angular diameter" => angular_diameter = parse_into(value)?,
+                _ => {}
+            }
+        }
+
+        let color = color.ok_or("missing required key `color`")?
+            * intensity.ok_or("missing required key `intensity`")?;
+
+        match member.trim() {
+            "point" => Ok(Light::Point {
+                position: position.ok_or("missing required key `position`")?,
+                color,
+            }),
+            "spot" => Ok(Light::Spot {
+                position: position.ok_or("missing required key `position`")?,
+                direction: direction.ok_or("missing required key `direction`")?,
+                falloff: SpotFalloff::new(angle.ok_or("missing required key `angle`")?, blend),
+                color,
+            }),
+            "directional" => Ok(Light::Directional {
+                direction: direction.ok_or("missing required key `direction`")?,
+                cos_max: (angular_diameter * 0.5).to_radians().cos(),
+                color,
+            }),
+            _ => Err(format!("unknown light type {member}"))?,
+        }
+    }
+}
@@ -1,10 +1,16 @@
-use std::{error::Error, path::Path};
+use std::{
+    error::Error,
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
 
-use crate::maths::vec3;
+use crate::maths::{vec2, vec3};
 
 pub struct RawMesh {
     pub vertices: Vec<vec3>,
     pub normals: Vec<vec3>,
+    pub texcoords: Vec<vec2>,
     pub indices: Vec<u32>,
 }
 
@@ -25,7 +31,85 @@ pub fn load_obj<P: AsRef<Path> + std::fmt::Debug>(path: P) -> Result<Vec<RawMesh
                 .chunks(3)
                 .map(|n| vec3::new(n[0], n[1], n[2]))
                 .collect(),
+            texcoords: model
+                .mesh
+                .texcoords
+                .chunks(2)
+                .map(|n| vec2::new(n[0], n[1]))
+                .collect(),
             indices: model.mesh.indices.clone(),
         })
         .collect::<Vec<_>>())
 }
+
+// writes a single mesh as an obj file, normals and texcoords only if it has them
+pub fn save_obj<P: AsRef<Path>>(path: P, mesh: &RawMesh) -> Result<(), Box<dyn Error>> {
+    let mut out = BufWriter::new(File::create(path)?);
+    for v in mesh.vertices.iter() {
+        writeln!(out, "v {} {} {}", v.x, v.y, v.z)?;
+    }
+    for n in mesh.normals.iter() {
+        writeln!(out, "vn {} {} {}", n.x, n.y, n.z)?;
+    }
+    for t in mesh.texcoords.iter() {
+        writeln!(out, "vt {} {}", t.x, t.y)?;
+    }
+
+    // obj indices start at one
+    for tri in mesh.indices.chunks(3) {
+        write!(out, "f")?;
+        for i in tri.iter().map(|i| i + 1) {
+            match (mesh.texcoords.is_empty(), mesh.normals.is_empty()) {
+                (true, true) => write!(out, " {i}")?,
+                (true, false) => write!(out, " {i}//{i}")?,
+                (false, true) => write!(out, " {i}/{i}")?,
+                (false, false) => write!(out, " {i}/{i}/{i}")?,
+            }
+        }
+        writeln!(out)?;
+    }
+
+    out.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn saved_meshes_load_back() {
+        // a tetrahedron, with normals pointing away from its middle
+        let vertices = vec![
+            vec3::new(0.0, 0.0, 0.0),
+            vec3::new(1.0, 0.0, 0.0),
+            vec3::new(0.0, 1.0, 0.0),
+            vec3::new(0.0, 0.0, 1.5),
+        ];
+        let mesh = RawMesh {
+            normals: vertices
+                .iter()
+                .map(|v| (*v - vec3::splat(0.25)).normalized())
+                .collect(),
+            vertices,
+            texcoords: vec![],
+            indices: vec![0, 2, 1, 0, 1, 3, 0, 3, 2, 1, 2, 3],
+        };
+
+        let path = std::env::temp_dir().join("heliochrome_saved_meshes_load_back.obj");
+        save_obj(&path, &mesh).unwrap();
+        let loaded = load_obj(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.len(), 1);
+        let loaded = &loaded[0];
+        // tobj numbers the vertices in the order the faces use them, so compare corners
+        assert_eq!(loaded.indices.len(), mesh.indices.len());
+        assert!(loaded.texcoords.is_empty());
+        for (a, b) in loaded.indices.iter().zip(mesh.indices.iter()) {
+            let (a, b) = (*a as usize, *b as usize);
+            assert!((loaded.vertices[a] - mesh.vertices[b]).mag() < 1e-6);
+            assert!((loaded.normals[a] - mesh.normals[b]).mag() < 1e-6);
+        }
+    }
+}
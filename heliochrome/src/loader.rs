@@ -42,6 +42,25 @@ pub fn collect_until_next_item<'a>(
     }
 }
 
+// like `collect_until_next_item` but for keys whose nested block is optional, such as textures
+// which can be given inline (`opacity: 0.5`), only lines indented past `parent` are collected.
+pub fn collect_nested_item<'a>(
+    parent: &str,
+    line_iter: &mut (impl Iterator<Item = &'a String> + Clone),
+) -> Vec<String> {
+    let indent = |s: &str| s.chars().take_while(|c| c.is_whitespace()).count();
+    let is_nested = line_iter
+        .clone()
+        .find(|x| !x.trim().is_empty())
+        .map_or(false, |x| indent(x) > indent(parent));
+
+    if is_nested {
+        collect_until_next_item(line_iter)
+    } else {
+        vec![]
+    }
+}
+
 pub fn parse_into<T: std::str::FromStr>(value: &str) -> Result<T, Box<dyn Error>>
 where
     <T as std::str::FromStr>::Err: std::fmt::Display,
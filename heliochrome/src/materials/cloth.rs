@@ -0,0 +1,115 @@
+use std::error::Error;
+
+use super::{Opacity, Scatter, ScatterType};
+use crate::{
+    color::Color,
+    hittables::BounceInfo,
+    loader::{collect_nested_item, parse_into, FromHCY},
+    materials::Scatterable,
+    maths::Ray,
+    pdf::CosinePdf,
+    texture::Texture,
+};
+
+// a diffuse base with a charlie sheen lobe and ashikhmin's visibility term on top, see
+// "Production Friendly Microfacet Sheen BRDF" (Estevez and Kulla)
+#[derive(Clone, Debug)]
+pub struct Cloth {
+    albedo: Color,
+    sheen: Color,
+    roughness: f32,
+    pub opacity: Option<Opacity>,
+}
+
+impl Cloth {
+    pub fn new(albedo: Color, sheen: Color, roughness: f32) -> Self {
+        Self {
+            albedo,
+            sheen,
+            roughness,
+            opacity: None,
+        }
+    }
+
+    fn charlie_d(&self, cos_h: f32) -> f32 {
+        let inv_alpha = 1.0 / (self.roughness * self.roughness).max(0.001);
+        let sin_h = (1.0 - cos_h * cos_h).max(0.0).sqrt();
+        (2.0 + inv_alpha) * sin_h.powf(inv_alpha) / std::f32::consts::TAU
+    }
+}
+
+impl Scatterable for Cloth {
+    fn scatter(&self, _ray: &Ray, hit: &BounceInfo) -> Option<Scatter> {
+        Some(Scatter {
+            attenuation: self.albedo,
+            scatter_type: ScatterType::Pdf(CosinePdf::new(hit.normal).into()),
+        })
+    }
+
+    fn pdf(&self, _incoming: &Ray, outgoing: &Ray, hit: &BounceInfo) -> f32 {
+        let cosine = hit.normal.dot(outgoing.direction.normalized());
+        if cosine > 0.0 {
+            cosine / std::f32::consts::PI
+        } else {
+            0.0
+        }
+    }
+
+    fn eval(&self, incoming: &Ray, outgoing: &Ray, hit: &BounceInfo, _attenuation: Color) -> Color {
+        let l = outgoing.direction.normalized();
+        let v = -incoming.direction.normalized();
+        let n_dot_l = hit.normal.dot(l);
+        if n_dot_l <= 0.0 {
+            return Color::splat(0.0);
+        }
+        let n_dot_v = hit.normal.dot(v).max(0.0);
+        let h = (l + v).normalized();
+
+        let d = self.charlie_d(hit.normal.dot(h));
+        let vis = 1.0 / (4.0 * (n_dot_l + n_dot_v - n_dot_l * n_dot_v));
+
+        (self.albedo / std::f32::consts::PI + self.sheen * d * vis) * n_dot_l
+    }
+
+    fn opacity(&self) -> Option<&Opacity> {
+        self.opacity.as_ref()
+    }
+}
+
+impl FromHCY for Cloth {
+    fn from_hcy(_member: Option<&str>, lines: Vec<String>) -> Result<Self, Box<dyn Error>> {
+        let mut albedo = None;
+        let mut sheen = None;
+        let mut roughness = Some(0.5);
+        let mut opacity = None;
+        let mut alpha_cutoff = None;
+
+        let mut line_iter = lines.iter();
+        while let Some(line) = line_iter.next() {
+            let (key, value) = line
+                .split_once(':')
+                .ok_or("invalid key value pair syntax")?;
+            match key.trim() {
+                "albedo" => albedo = Some(parse_into(value)?),
+                "sheen" => sheen = Some(parse_into(value)?),
+                "roughness" => roughness = Some(parse_into(value)?),
+                "opacity" => {
+                    opacity = Some(
+                        Texture::from_hcy(Some(value), collect_nested_item(line, &mut line_iter))
+                            .map_err(|err| format!("could not parse opacity key: {err}"))?,
+                    )
+                }
+                "alpha cutoff" => alpha_cutoff = Some(parse_into(value)?),
+                _ => {}
+            }
+        }
+
+        let mut cloth = Cloth::new(
+            albedo.ok_or("missing required key `albedo`")?,
+            sheen.ok_or("missing required key `sheen`")?,
+            roughness.unwrap(),
+        );
+        cloth.opacity = Opacity::from_keys(opacity, alpha_cutoff);
+        Ok(cloth)
+    }
+}
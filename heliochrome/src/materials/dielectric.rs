@@ -2,28 +2,36 @@ use std::error::Error;
 
 use rand::random;
 
-use super::ScatterType;
+use super::{Opacity, ScatterType, ThinFilm};
 use crate::{
     color::Color,
     hittables::BounceInfo,
-    loader::{parse_into, FromHCY},
+    loader::{collect_nested_item, parse_into, FromHCY},
     materials::{Scatter, Scatterable},
     maths::Ray,
+    texture::Texture,
 };
 
 #[derive(Clone, Debug)]
 pub struct Dielectric {
     pub ir: f32,
     pub color: Color,
+    pub opacity: Option<Opacity>,
+    pub thin_film: Option<ThinFilm>,
 }
 
 impl Dielectric {
     pub fn new(ir: f32, color: Color) -> Self {
-        Self { ir, color }
+        Self {
+            ir,
+            color,
+            opacity: None,
+            thin_film: None,
+        }
     }
 }
 
-fn reflectance(cosine: f32, ref_idx: f32) -> f32 {
+pub(crate) fn reflectance(cosine: f32, ref_idx: f32) -> f32 {
     let r0 = ((1.0 - ref_idx) / (1.0 + ref_idx)).powi(2);
     r0 + (1.0 - r0) * (1.0 - cosine).powi(5)
 }
@@ -39,6 +47,32 @@ impl Scatterable for Dielectric {
         let cos_theta = (-unit_dir).dot(hit.normal).min(1.0);
         let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
         let cannot_reflect = refraction_ratio * sin_theta > 1.0;
+
+        if let Some(film) = &self.thin_film {
+            // the film reflects each channel differently, so pick a lobe with the average
+            // reflectance and reweight the channels to keep it unbiased.
+            let (n1, n3) = if hit.front_face {
+                (1.0, self.ir)
+            } else {
+                (self.ir, 1.0)
+            };
+            let r = film.reflectance(cos_theta, n1, n3);
+            let p = (r.r + r.g + r.b) / 3.0;
+            let (direction, attenuation) = if cannot_reflect || p > random() {
+                (unit_dir.reflect_over(hit.normal), self.color * r / p)
+            } else {
+                (
+                    unit_dir.refract(hit.normal, refraction_ratio),
+                    self.color * (Color::splat(1.0) - r) / (1.0 - p),
+                )
+            };
+
+            return Some(Scatter {
+                attenuation,
+                scatter_type: ScatterType::Specular(Ray::new(hit.p, direction)),
+            });
+        }
+
         let direction = if cannot_reflect || reflectance(cos_theta, refraction_ratio) > random() {
             unit_dir.reflect_over(hit.normal)
         } else {
@@ -54,27 +88,46 @@ impl Scatterable for Dielectric {
     fn is_important(&self) -> bool {
         false
     }
+
+    fn opacity(&self) -> Option<&Opacity> {
+        self.opacity.as_ref()
+    }
 }
 
 impl FromHCY for Dielectric {
     fn from_hcy(_member: Option<&str>, lines: Vec<String>) -> Result<Self, Box<dyn Error>> {
         let mut ir = None;
         let mut color = Some(Color::splat(1.0));
+        let mut opacity = None;
+        let mut alpha_cutoff = None;
+        let mut film_thickness = None;
+        let mut film_ir = None;
 
-        for line in lines.into_iter() {
+        let mut line_iter = lines.iter();
+        while let Some(line) = line_iter.next() {
             let (key, value) = line
                 .split_once(':')
                 .ok_or("invalid key value pair syntax")?;
             match key.trim() {
                 "ir" => ir = Some(parse_into(value)?),
                 "color" => color = Some(parse_into(value)?),
+                "opacity" => {
+                    opacity = Some(
+                        Texture::from_hcy(Some(value), collect_nested_item(line, &mut line_iter))
+                            .map_err(|err| format!("could not parse opacity key: {err}"))?,
+                    )
+                }
+                "alpha cutoff" => alpha_cutoff = Some(parse_into(value)?),
+                "film thickness" => film_thickness = Some(parse_into(value)?),
+                "film ir" => film_ir = Some(parse_into(value)?),
                 _ => {}
             }
         }
 
-        Ok(Dielectric::new(
-            ir.ok_or("missing required key `ir`")?,
-            color.unwrap(),
-        ))
+        let mut dielectric =
+            Dielectric::new(ir.ok_or("missing required key `ir`")?, color.unwrap());
+        dielectric.opacity = Opacity::from_keys(opacity, alpha_cutoff);
+        dielectric.thin_film = ThinFilm::from_keys(film_thickness, film_ir);
+        Ok(dielectric)
     }
 }
@@ -1,23 +1,64 @@
 use std::error::Error;
 
-use super::Scatter;
+use super::{Opacity, Scatter};
 use crate::{
     color::Color,
     hittables::BounceInfo,
-    loader::{parse_into, FromHCY},
+    loader::{collect_nested_item, parse_into, FromHCY},
     materials::Scatterable,
     maths::Ray,
+    texture::Texture,
 };
 
+#[derive(Clone, Copy, Debug)]
+pub struct SpotFalloff {
+    // cosines of the angles from the surface normal where the falloff ends and starts
+    cos_outer: f32,
+    cos_inner: f32,
+}
+
+impl SpotFalloff {
+    pub fn new(angle: f32, blend: f32) -> Self {
+        Self {
+            cos_outer: angle.to_radians().cos(),
+            cos_inner: (angle - blend).max(0.0).to_radians().cos(),
+        }
+    }
+
+    pub fn falloff(&self, cos_theta: f32) -> f32 {
+        if self.cos_inner <= self.cos_outer {
+            return if cos_theta >= self.cos_outer {
+                1.0
+            } else {
+                0.0
+            };
+        }
+        let t = ((cos_theta - self.cos_outer) / (self.cos_inner - self.cos_outer)).clamp(0.0, 1.0);
+        t * t * (3.0 - 2.0 * t)
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct DiffuseLight {
-    color: Color,
+    emission: Texture,
+    intensity: f32,
+    pub two_sided: bool,
+    pub spot: Option<SpotFalloff>,
+    pub opacity: Option<Opacity>,
 }
 
 impl DiffuseLight {
     pub fn new(color: Color, intensity: f32) -> Self {
+        Self::new_textured(Texture::Solid(color), intensity)
+    }
+
+    pub fn new_textured(emission: Texture, intensity: f32) -> Self {
         Self {
-            color: color * intensity,
+            emission,
+            intensity,
+            two_sided: false,
+            spot: None,
+            opacity: None,
         }
     }
 }
@@ -27,42 +68,75 @@ impl Scatterable for DiffuseLight {
         None
     }
 
-    fn emitted(&self, hit: &BounceInfo) -> Color {
-        if hit.front_face {
-            self.color
-        } else {
-            Color::splat(0.0)
+    fn emitted(&self, ray: &Ray, hit: &BounceInfo) -> Color {
+        if !hit.front_face && !self.two_sided {
+            return Color::splat(0.0);
+        }
+
+        let mut color = self.emission.sample(&hit.uv, &hit.p) * self.intensity;
+        if let Some(spot) = &self.spot {
+            // the normal always faces the incoming ray so this is the emission angle
+            color *= spot.falloff((-ray.direction.normalized()).dot(hit.normal));
         }
+
+        color
     }
 
     fn is_important(&self) -> bool {
         true
     }
+
+    fn opacity(&self) -> Option<&Opacity> {
+        self.opacity.as_ref()
+    }
 }
 
 impl FromHCY for DiffuseLight {
     fn from_hcy(_member: Option<&str>, lines: Vec<String>) -> Result<Self, Box<dyn Error>> {
         let mut color = None;
         let mut intensity = None;
+        let mut two_sided = false;
+        let mut spot_angle = None;
+        let mut spot_blend = 0.0;
+        let mut opacity = None;
+        let mut alpha_cutoff = None;
 
-        for line in lines.into_iter() {
+        let mut line_iter = lines.iter();
+        while let Some(line) = line_iter.next() {
             let (key, value) = line
                 .split_once(':')
                 .ok_or("invalid key value pair syntax")?;
             match key.trim() {
                 "color" => {
-                    color = Some(parse_into(value)?);
+                    color = Some(
+                        Texture::from_hcy(Some(value), collect_nested_item(line, &mut line_iter))
+                            .map_err(|err| format!("could not parse color key: {err}"))?,
+                    );
                 }
                 "intensity" => {
                     intensity = Some(parse_into(value)?);
                 }
+                "two sided" => two_sided = parse_into(value)?,
+                "spot angle" => spot_angle = Some(parse_into(value)?),
+                "spot blend" => spot_blend = parse_into(value)?,
+                "opacity" => {
+                    opacity = Some(
+                        Texture::from_hcy(Some(value), collect_nested_item(line, &mut line_iter))
+                            .map_err(|err| format!("could not parse opacity key: {err}"))?,
+                    )
+                }
+                "alpha cutoff" => alpha_cutoff = Some(parse_into(value)?),
                 _ => {}
             }
         }
 
-        Ok(Self::new(
+        let mut light = Self::new_textured(
             color.ok_or("missing required key `color`")?,
             intensity.ok_or("missing required key `intensity`")?,
-        ))
+        );
+        light.two_sided = two_sided;
+        light.spot = spot_angle.map(|angle| SpotFalloff::new(angle, spot_blend));
+        light.opacity = Opacity::from_keys(opacity, alpha_cutoff);
+        Ok(light)
     }
 }
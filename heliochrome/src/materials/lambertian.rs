@@ -1,23 +1,27 @@
 use std::error::Error;
 
-use super::{Scatter, ScatterType};
+use super::{Opacity, Scatter, ScatterType};
 use crate::{
-    color::Color,
     hittables::BounceInfo,
-    loader::{parse_into, FromHCY},
+    loader::{collect_nested_item, parse_into, FromHCY},
     materials::Scatterable,
     maths::Ray,
     pdf::CosinePdf,
+    texture::Texture,
 };
 
 #[derive(Clone, Debug)]
 pub struct Lambertian {
-    albedo: Color,
+    albedo: Texture,
+    pub opacity: Option<Opacity>,
 }
 
 impl Lambertian {
-    pub fn new(albedo: Color) -> Self {
-        Lambertian { albedo }
+    pub fn new<T: Into<Texture>>(albedo: T) -> Self {
+        Lambertian {
+            albedo: albedo.into(),
+            opacity: None,
+        }
     }
 }
 
@@ -27,7 +31,7 @@ impl Scatterable for Lambertian {
         // let dir = uvw.local(&vec3::random_cosine_direction());
         Some(Scatter {
             // outgoing: Ray::new(hit.p, dir),
-            attenuation: self.albedo,
+            attenuation: self.albedo.sample_hit(hit),
             scatter_type: ScatterType::Pdf(CosinePdf::new(hit.normal).into()),
         })
     }
@@ -40,23 +44,43 @@ impl Scatterable for Lambertian {
             0.0
         }
     }
+
+    fn opacity(&self) -> Option<&Opacity> {
+        self.opacity.as_ref()
+    }
 }
 
 impl FromHCY for Lambertian {
     fn from_hcy(_member: Option<&str>, lines: Vec<String>) -> Result<Self, Box<dyn Error>> {
         let mut albedo = None;
+        let mut opacity = None;
+        let mut alpha_cutoff = None;
 
-        for line in lines.into_iter() {
+        let mut line_iter = lines.iter();
+        while let Some(line) = line_iter.next() {
             let (key, value) = line
                 .split_once(':')
                 .ok_or("invalid key value pair syntax")?;
-            if "albedo" == key.trim() {
-                albedo = Some(parse_into(value)?)
+            match key.trim() {
+                "albedo" => {
+                    albedo = Some(
+                        Texture::from_hcy(Some(value), collect_nested_item(line, &mut line_iter))
+                            .map_err(|err| format!("could not parse albedo key: {err}"))?,
+                    )
+                }
+                "opacity" => {
+                    opacity = Some(
+                        Texture::from_hcy(Some(value), collect_nested_item(line, &mut line_iter))
+                            .map_err(|err| format!("could not parse opacity key: {err}"))?,
+                    )
+                }
+                "alpha cutoff" => alpha_cutoff = Some(parse_into(value)?),
+                _ => {}
             }
         }
 
-        Ok(Lambertian::new(
-            albedo.ok_or("missing required key `albedo`")?,
-        ))
+        let mut lambertian = Lambertian::new(albedo.ok_or("missing required key `albedo`")?);
+        lambertian.opacity = Opacity::from_keys(opacity, alpha_cutoff);
+        Ok(lambertian)
     }
 }
@@ -1,23 +1,31 @@
 use std::error::Error;
 
-use super::ScatterType;
+use super::{Opacity, ScatterType, ThinFilm};
 use crate::{
     color::Color,
     hittables::BounceInfo,
-    loader::{parse_into, FromHCY},
+    loader::{collect_nested_item, parse_into, FromHCY},
     materials::{Scatter, Scatterable},
     maths::{vec3, Ray},
+    texture::Texture,
 };
 
 #[derive(Clone, Debug)]
 pub struct Metal {
     albedo: Color,
     fuzz: f32,
+    pub opacity: Option<Opacity>,
+    pub thin_film: Option<ThinFilm>,
 }
 
 impl Metal {
     pub fn new(albedo: Color, fuzz: f32) -> Metal {
-        Self { albedo, fuzz }
+        Self {
+            albedo,
+            fuzz,
+            opacity: None,
+            thin_film: None,
+        }
     }
 }
 
@@ -25,9 +33,16 @@ impl Scatterable for Metal {
     fn scatter(&self, ray: &Ray, hit: &BounceInfo) -> Option<Scatter> {
         let reflected = ray.direction.reflect_over(hit.normal);
         if reflected.dot(hit.normal) > 0.0 {
+            let attenuation = if let Some(film) = &self.thin_film {
+                let cos_theta = (-ray.direction.normalized()).dot(hit.normal).min(1.0);
+                film.reflectance_metal(cos_theta, 1.0, self.albedo)
+            } else {
+                self.albedo
+            };
+
             Some(Scatter {
                 // outgoing: Ray::new(hit.p, reflected + self.fuzz * vec3::random_in_unit_sphere()),
-                attenuation: self.albedo,
+                attenuation,
                 scatter_type: ScatterType::Specular(Ray::new(
                     hit.p,
                     reflected + self.fuzz * vec3::random_in_unit_sphere(),
@@ -37,27 +52,48 @@ impl Scatterable for Metal {
             None
         }
     }
+
+    fn opacity(&self) -> Option<&Opacity> {
+        self.opacity.as_ref()
+    }
 }
 
 impl FromHCY for Metal {
     fn from_hcy(_member: Option<&str>, lines: Vec<String>) -> Result<Self, Box<dyn Error>> {
         let mut albedo = None;
         let mut fuzz = Some(0.0);
+        let mut opacity = None;
+        let mut alpha_cutoff = None;
+        let mut film_thickness = None;
+        let mut film_ir = None;
 
-        for line in lines.into_iter() {
+        let mut line_iter = lines.iter();
+        while let Some(line) = line_iter.next() {
             let (key, value) = line
                 .split_once(':')
                 .ok_or("invalid key value pair syntax")?;
             match key.trim() {
                 "albedo" => albedo = Some(parse_into(value)?),
                 "fuzz" => fuzz = Some(parse_into(value)?),
+                "opacity" => {
+                    opacity = Some(
+                        Texture::from_hcy(Some(value), collect_nested_item(line, &mut line_iter))
+                            .map_err(|err| format!("could not parse opacity key: {err}"))?,
+                    )
+                }
+                "alpha cutoff" => alpha_cutoff = Some(parse_into(value)?),
+                "film thickness" => film_thickness = Some(parse_into(value)?),
+                "film ir" => film_ir = Some(parse_into(value)?),
                 _ => {}
             }
         }
 
-        Ok(Metal::new(
+        let mut metal = Metal::new(
             albedo.ok_or("missing required key `albedo`")?,
             fuzz.unwrap(),
-        ))
+        );
+        metal.opacity = Opacity::from_keys(opacity, alpha_cutoff);
+        metal.thin_film = ThinFilm::from_keys(film_thickness, film_ir);
+        Ok(metal)
     }
 }
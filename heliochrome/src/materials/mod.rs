@@ -5,6 +5,12 @@ use enum_dispatch::enum_dispatch;
 mod scatterable;
 pub use scatterable::*;
 
+mod opacity;
+pub use opacity::*;
+
+mod thin_film;
+pub use thin_film::*;
+
 mod lambertian;
 pub use lambertian::*;
 
@@ -17,7 +23,19 @@ pub use dielectric::*;
 mod diffuse_light;
 pub use diffuse_light::*;
 
-use crate::{color::Color, hittables::BounceInfo, loader::FromHCY, maths::Ray};
+mod subsurface;
+pub use subsurface::*;
+
+mod cloth;
+pub use cloth::*;
+
+use crate::{
+    color::Color,
+    hittables::BounceInfo,
+    loader::FromHCY,
+    maths::{vec3, Ray},
+    object::Object,
+};
 
 #[enum_dispatch(Scatterable)]
 #[derive(Clone, Debug)]
@@ -26,6 +44,8 @@ pub enum Material {
     Metal,
     Dielectric,
     DiffuseLight,
+    Subsurface,
+    Cloth,
 }
 
 impl FromHCY for Material {
@@ -36,6 +56,8 @@ impl FromHCY for Material {
             "metal" => Ok(Material::Metal(Metal::from_hcy(None, lines)?)),
             "dielectric" => Ok(Material::Dielectric(Dielectric::from_hcy(None, lines)?)),
             "diffuse_light" => Ok(Material::DiffuseLight(DiffuseLight::from_hcy(None, lines)?)),
+            "subsurface" => Ok(Material::Subsurface(Subsurface::from_hcy(None, lines)?)),
+            "cloth" => Ok(Material::Cloth(Cloth::from_hcy(None, lines)?)),
             _ => Err(format!("unknown material {member}"))?,
         }
     }
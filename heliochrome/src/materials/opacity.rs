@@ -0,0 +1,60 @@
+use crate::{hittables::BounceInfo, texture::Texture};
+
+#[derive(Clone, Debug)]
+pub struct Opacity {
+    pub mask: Texture,
+    // when set the mask is a hard alpha test, otherwise it is used as a probability by the
+    // integrator and only cut at one half when testing for hits
+    pub cutoff: Option<f32>,
+}
+
+impl Opacity {
+    pub fn new(mask: Texture, cutoff: Option<f32>) -> Self {
+        Self { mask, cutoff }
+    }
+
+    pub fn from_keys(mask: Option<Texture>, cutoff: Option<f32>) -> Option<Self> {
+        mask.map(|mask| Self::new(mask, cutoff))
+    }
+
+    fn alpha(&self, hit: &BounceInfo) -> f32 {
+        self.mask.sample(&hit.uv, &hit.p).luminance()
+    }
+
+    // whether hit tests skip the surface here, this has to give the same answer every time
+    // so shadow rays and light pdfs agree with each other
+    pub fn cuts_out(&self, hit: &BounceInfo) -> bool {
+        self.alpha(hit) < self.cutoff.unwrap_or(0.5)
+    }
+
+    // whether a ray that did hit the surface carries on through it anyway, only stochastic
+    // masks let any through
+    pub fn passes_through(&self, hit: &BounceInfo) -> bool {
+        self.cutoff.is_none() && self.alpha(hit) < rand::random()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        color::Color,
+        hittables::{Hittable, Sphere},
+        materials::Lambertian,
+        maths::{vec3, Ray},
+        object::Object,
+    };
+
+    #[test]
+    fn hit_tests_are_deterministic() {
+        let ray = Ray::new(vec3::new(0.0, 0.0, -5.0), vec3::unit_z());
+        for (alpha, hit) in [(0.4, false), (0.7, true)] {
+            let mut material = Lambertian::new(Color::splat(0.5));
+            material.opacity = Some(Opacity::new(Color::splat(alpha).into(), None));
+            let object = Object::new(Sphere::new(vec3::splat(0.0), 1.0), material, None);
+            for _ in 0..64 {
+                assert_eq!(object.intersect(&ray, 0.001, f32::INFINITY).is_some(), hit);
+            }
+        }
+    }
+}
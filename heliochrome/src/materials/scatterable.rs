@@ -1,10 +1,19 @@
 use enum_dispatch::enum_dispatch;
 
-use crate::{color::Color, hittables::BounceInfo, maths::Ray, pdf::Pdf};
+use super::Opacity;
+use crate::{
+    color::Color,
+    hittables::BounceInfo,
+    maths::{vec3, Ray},
+    object::Object,
+    pdf::Pdf,
+};
 
 pub enum ScatterType<'a> {
     Pdf(Pdf<'a>),
     Specular(Ray),
+    // the ray has entered the object and should continue with `Scatterable::random_walk`
+    Subsurface(Ray),
 }
 
 pub struct Scatter<'a> {
@@ -21,11 +30,26 @@ pub trait Scatterable: Clone {
         0.0
     }
 
-    fn emitted(&self, hit: &BounceInfo) -> Color {
+    // the scattered color (brdf * cosine) for a pdf scatter, materials whose lobes are not
+    // just a tint of their pdf override this.
+    fn eval(&self, incoming: &Ray, outgoing: &Ray, hit: &BounceInfo, attenuation: Color) -> Color {
+        self.pdf(incoming, outgoing, hit) * attenuation
+    }
+
+    // the ray leaving the object, the weight of the walk and the outward normal where it left
+    fn random_walk(&self, entered: &Ray, object: &Object) -> Option<(Ray, Color, vec3)> {
+        None
+    }
+
+    fn emitted(&self, ray: &Ray, hit: &BounceInfo) -> Color {
         Color::splat(0.0)
     }
 
     fn is_important(&self) -> bool {
         false
     }
+
+    fn opacity(&self) -> Option<&Opacity> {
+        None
+    }
 }
@@ -0,0 +1,193 @@
+use std::error::Error;
+
+use rand::random;
+
+use super::{reflectance, Opacity, ScatterType};
+use crate::{
+    color::Color,
+    hittables::{BounceInfo, Hittable},
+    loader::{collect_nested_item, parse_into, FromHCY},
+    materials::{Scatter, Scatterable},
+    maths::{vec3, Ray, ONB},
+    object::Object,
+    texture::Texture,
+};
+
+const MAX_WALK_STEPS: u16 = 256;
+const WALK_EPSILON: f32 = 0.001;
+
+#[derive(Clone, Debug)]
+pub struct Subsurface {
+    pub ir: f32,
+    pub albedo: Color,
+    // mean free path of each channel, larger values let that channel travel further inside.
+    pub radius: Color,
+    pub opacity: Option<Opacity>,
+}
+
+impl Subsurface {
+    pub fn new(ir: f32, albedo: Color, radius: Color) -> Self {
+        Self {
+            ir,
+            albedo,
+            radius,
+            opacity: None,
+        }
+    }
+
+    fn boundary(&self, unit_dir: vec3, hit: &BounceInfo) -> (vec3, bool) {
+        let refraction_ratio = if hit.front_face {
+            1.0 / self.ir
+        } else {
+            self.ir
+        };
+        let cos_theta = (-unit_dir).dot(hit.normal).min(1.0);
+        let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
+        let cannot_refract = refraction_ratio * sin_theta > 1.0;
+        if cannot_refract || reflectance(cos_theta, refraction_ratio) > random() {
+            (unit_dir.reflect_over(hit.normal), false)
+        } else {
+            (unit_dir.refract(hit.normal, refraction_ratio), true)
+        }
+    }
+}
+
+impl Scatterable for Subsurface {
+    fn scatter(&self, ray: &Ray, hit: &BounceInfo) -> Option<Scatter> {
+        let (direction, refracted) = self.boundary(ray.direction.normalized(), hit);
+        let scattered = Ray::new(hit.p, direction);
+
+        Some(Scatter {
+            attenuation: Color::splat(1.0),
+            scatter_type: if refracted && hit.front_face {
+                ScatterType::Subsurface(scattered)
+            } else {
+                ScatterType::Specular(scattered)
+            },
+        })
+    }
+
+    fn random_walk(&self, entered: &Ray, object: &Object) -> Option<(Ray, Color, vec3)> {
+        let sigma_t = Color::splat(1.0) / self.radius.max(&Color::splat(f32::EPSILON));
+        let mut ray =
+            Ray::new_at_time(entered.origin, entered.direction.normalized(), entered.time);
+        let mut weight = Color::splat(1.0);
+        // only a ray starting on the wall can hit it again straight away
+        let mut t_min = WALK_EPSILON;
+
+        for _ in 0..MAX_WALK_STEPS {
+            // distances are sampled from one channel and weighted by the average over all of
+            // them so no channel's contribution is lost.
+            let channel = (random::<f32>() * 3.0) as usize % 3;
+            let dist = -(1.0 - random::<f32>()).ln() / sigma_t[channel];
+
+            if let Some(intersection) = object.intersect(&ray, t_min, dist) {
+                let transmittance = (-sigma_t * intersection.t).exp();
+                let pdf = (transmittance.r + transmittance.g + transmittance.b) / 3.0;
+                weight *= transmittance / pdf;
+
+                let hit = object.get_bounce_info(&ray, intersection);
+                let (direction, refracted) = self.boundary(ray.direction, &hit);
+                ray = Ray::new_at_time(hit.p, direction, entered.time);
+                t_min = WALK_EPSILON;
+                if refracted {
+                    // seen from inside the normal faces in
+                    return Some((ray, weight, -hit.normal));
+                }
+            } else {
+                let transmittance = (-sigma_t * dist).exp();
+                let density = sigma_t * transmittance;
+                let pdf = (density.r + density.g + density.b) / 3.0;
+                weight *= self.albedo * density / pdf;
+
+                ray = Ray::new_at_time(
+                    ray.at(dist),
+                    vec3::random_in_unit_sphere().normalized(),
+                    entered.time,
+                );
+                t_min = 0.0;
+            }
+        }
+
+        // out of steps, leave through the wall in a random direction like a diffuse surface
+        // would rather than losing the light
+        let to_wall = Ray::new_at_time(
+            ray.origin,
+            vec3::random_in_unit_sphere().normalized(),
+            entered.time,
+        );
+        // the walk can end closer to the wall than the epsilon the steps use
+        let intersection = object.intersect(&to_wall, 0.0, f32::INFINITY)?;
+        let hit = object.get_bounce_info(&to_wall, intersection);
+        let normal = -hit.normal;
+        let direction = ONB::new_from_w(normal).local(&vec3::random_cosine_direction());
+        Some((
+            Ray::new_at_time(hit.p, direction, entered.time),
+            weight,
+            normal,
+        ))
+    }
+
+    fn opacity(&self) -> Option<&Opacity> {
+        self.opacity.as_ref()
+    }
+}
+
+impl FromHCY for Subsurface {
+    fn from_hcy(_member: Option<&str>, lines: Vec<String>) -> Result<Self, Box<dyn Error>> {
+        let mut ir = None;
+        let mut albedo = None;
+        let mut radius = None;
+        let mut opacity = None;
+        let mut alpha_cutoff = None;
+
+        let mut line_iter = lines.iter();
+        while let Some(line) = line_iter.next() {
+            let (key, value) = line
+                .split_once(':')
+                .ok_or("invalid key value pair syntax")?;
+            match key.trim() {
+                "ir" => ir = Some(parse_into(value)?),
+                "albedo" => albedo = Some(parse_into(value)?),
+                "radius" => radius = Some(parse_into(value)?),
+                "opacity" => {
+                    opacity = Some(
+                        Texture::from_hcy(Some(value), collect_nested_item(line, &mut line_iter))
+                            .map_err(|err| format!("could not parse opacity key: {err}"))?,
+                    )
+                }
+                "alpha cutoff" => alpha_cutoff = Some(parse_into(value)?),
+                _ => {}
+            }
+        }
+
+        let mut subsurface = Subsurface::new(
+            ir.ok_or("missing required key `ir`")?,
+            albedo.ok_or("missing required key `albedo`")?,
+            radius.ok_or("missing required key `radius`")?,
+        );
+        subsurface.opacity = Opacity::from_keys(opacity, alpha_cutoff);
+        Ok(subsurface)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::hittables::Sphere;
+
+    #[test]
+    fn walks_always_leave_the_surface() {
+        // far too dense to get out within the step budget
+        let material = Subsurface::new(1.3, Color::splat(1.0), Color::splat(0.002));
+        let object = Object::new(Sphere::new(vec3::splat(0.0), 1.0), material.clone(), None);
+        let entered = Ray::new(vec3::new(0.0, 1.0, 0.0), -vec3::unit_y());
+
+        for _ in 0..8 {
+            let (exit, weight, normal) = material.random_walk(&entered, &object).unwrap();
+            assert!((exit.origin.mag() - 1.0).abs() < 0.001);
+            assert!(exit.direction.dot(normal) > 0.0);
+            assert!(weight.r > 0.0);
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use crate::color::Color;
+
+// wavelengths in nm used for the r, g and b channels
+const WAVELENGTHS: [f32; 3] = [650.0, 532.0, 450.0];
+
+#[derive(Clone, Copy, Debug)]
+pub struct ThinFilm {
+    // in nm
+    pub thickness: f32,
+    pub ir: f32,
+}
+
+// fresnel amplitude coefficients for s and p polarized light
+fn fresnel_amplitudes(n1: f32, cos1: f32, n2: f32, cos2: f32) -> (f32, f32) {
+    (
+        (n1 * cos1 - n2 * cos2) / (n1 * cos1 + n2 * cos2),
+        (n2 * cos1 - n1 * cos2) / (n2 * cos1 + n1 * cos2),
+    )
+}
+
+// reflectance of the two interfaces of the film summed over all the internal bounces
+fn airy(r12: f32, r23: f32, cos_delta: f32) -> f32 {
+    let num = r12 * r12 + r23 * r23 + 2.0 * r12 * r23 * cos_delta;
+    let den = 1.0 + r12 * r12 * r23 * r23 + 2.0 * r12 * r23 * cos_delta;
+    (num / den).clamp(0.0, 1.0)
+}
+
+impl ThinFilm {
+    pub fn new(thickness: f32, ir: f32) -> Self {
+        Self { thickness, ir }
+    }
+
+    pub fn from_keys(thickness: Option<f32>, ir: Option<f32>) -> Option<Self> {
+        Some(Self::new(thickness?, ir.unwrap_or(1.33)))
+    }
+
+    // cosine of the angle inside the film, `None` if light is totally internally reflected
+    fn film_cos(&self, cos_i: f32, n1: f32) -> Option<f32> {
+        let sin_f = n1 / self.ir * (1.0 - cos_i * cos_i).max(0.0).sqrt();
+        if sin_f >= 1.0 {
+            None
+        } else {
+            Some((1.0 - sin_f * sin_f).sqrt())
+        }
+    }
+
+    fn phase_cos(&self, cos_f: f32, wavelength: f32) -> f32 {
+        (std::f32::consts::TAU / wavelength * 2.0 * self.ir * self.thickness * cos_f).cos()
+    }
+
+    // reflectance of the film sitting on top of a dielectric with index `n3`, seen from a
+    // medium with index `n1`.
+    pub fn reflectance(&self, cos_i: f32, n1: f32, n3: f32) -> Color {
+        let Some(cos_f) = self.film_cos(cos_i, n1) else {
+            return Color::splat(1.0);
+        };
+        let sin_3 = n1 / n3 * (1.0 - cos_i * cos_i).max(0.0).sqrt();
+        if sin_3 >= 1.0 {
+            return Color::splat(1.0);
+        }
+        let cos_3 = (1.0 - sin_3 * sin_3).sqrt();
+
+        let (r12_s, r12_p) = fresnel_amplitudes(n1, cos_i, self.ir, cos_f);
+        let (r23_s, r23_p) = fresnel_amplitudes(self.ir, cos_f, n3, cos_3);
+
+        let mut r = Color::default();
+        for (i, wavelength) in WAVELENGTHS.iter().enumerate() {
+            let cos_delta = self.phase_cos(cos_f, *wavelength);
+            r[i] = 0.5 * (airy(r12_s, r23_s, cos_delta) + airy(r12_p, r23_p, cos_delta));
+        }
+
+        r
+    }
+
+    // reflectance of the film over a metal, the metal is approximated as a conductor with
+    // reflectance `f0` at every angle, which is enough for the interference colours.
+    pub fn reflectance_metal(&self, cos_i: f32, n1: f32, f0: Color) -> Color {
+        let Some(cos_f) = self.film_cos(cos_i, n1) else {
+            return Color::splat(1.0);
+        };
+
+        let (r12_s, r12_p) = fresnel_amplitudes(n1, cos_i, self.ir, cos_f);
+
+        let mut r = Color::default();
+        for (i, wavelength) in WAVELENGTHS.iter().enumerate() {
+            let cos_delta = self.phase_cos(cos_f, *wavelength);
+            let r23 = -f0[i].clamp(0.0, 1.0).sqrt();
+            r[i] = 0.5 * (airy(r12_s, r23, cos_delta) + airy(r12_p, r23, cos_delta));
+        }
+
+        r
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn vanishing_film_is_plain_fresnel() {
+        // with no thickness the film drops out and normal incidence gives ((n1 - n3) / (n1 + n3))^2
+        let film = ThinFilm::new(0.0, 1.8);
+        let r = film.reflectance(1.0, 1.0, 1.5);
+        let expected = ((1.0 - 1.5f32) / (1.0 + 1.5)).powi(2);
+        for i in 0..3 {
+            assert!((r[i] - expected).abs() < 1e-5, "{} != {expected}", r[i]);
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use super::vec2;
+
+// piecewise constant distribution over [0, 1), see pbrt's Distribution1D
+#[derive(Clone, Debug)]
+pub struct Distribution1D {
+    func: Vec<f32>,
+    cdf: Vec<f32>,
+    pub integral: f32,
+}
+
+impl Distribution1D {
+    pub fn new(func: Vec<f32>) -> Self {
+        let n = func.len();
+        let mut cdf = vec![0.0; n + 1];
+        for i in 0..n {
+            cdf[i + 1] = cdf[i] + func[i].max(0.0) / n as f32;
+        }
+
+        let integral = cdf[n];
+        if integral == 0.0 {
+            // nothing to importance sample, fall back to uniform
+            for (i, c) in cdf.iter_mut().enumerate() {
+                *c = i as f32 / n as f32;
+            }
+        } else {
+            for c in cdf.iter_mut() {
+                *c /= integral;
+            }
+        }
+
+        Self {
+            func,
+            cdf,
+            integral,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.func.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.func.is_empty()
+    }
+
+    // returns the sampled point, its pdf and the index of the bucket it fell in
+    pub fn sample(&self, u: f32) -> (f32, f32, usize) {
+        let offset = (self.cdf.partition_point(|c| *c <= u).max(1) - 1).min(self.len() - 1);
+        let width = self.cdf[offset + 1] - self.cdf[offset];
+        let du = if width > 0.0 {
+            (u - self.cdf[offset]) / width
+        } else {
+            0.0
+        };
+
+        (
+            (offset as f32 + du) / self.len() as f32,
+            self.pdf(offset),
+            offset,
+        )
+    }
+
+    pub fn pdf(&self, offset: usize) -> f32 {
+        if self.integral == 0.0 {
+            1.0
+        } else {
+            self.func[offset].max(0.0) / self.integral
+        }
+    }
+
+    pub fn offset(&self, x: f32) -> usize {
+        ((x * self.len() as f32) as usize).min(self.len() - 1)
+    }
+}
+
+// piecewise constant distribution over [0, 1)^2, sampling a row from the marginal and then a
+// column from that row's conditional distribution.
+#[derive(Clone, Debug)]
+pub struct Distribution2D {
+    conditional: Vec<Distribution1D>,
+    marginal: Distribution1D,
+}
+
+impl Distribution2D {
+    // `func` is laid out row by row with `width` values in each row
+    pub fn new(func: &[f32], width: usize) -> Self {
+        let conditional = func
+            .chunks(width)
+            .map(|row| Distribution1D::new(row.to_vec()))
+            .collect::<Vec<_>>();
+        let marginal = Distribution1D::new(conditional.iter().map(|c| c.integral).collect());
+
+        Self {
+            conditional,
+            marginal,
+        }
+    }
+
+    pub fn sample(&self, u: &vec2) -> (vec2, f32) {
+        let (y, pdf_y, row) = self.marginal.sample(u.y);
+        let (x, pdf_x, _) = self.conditional[row].sample(u.x);
+        (vec2::new(x, y), pdf_x * pdf_y)
+    }
+
+    pub fn pdf(&self, p: &vec2) -> f32 {
+        let row = self.marginal.offset(p.y);
+        let col = self.conditional[row].offset(p.x);
+        self.conditional[row].pdf(col) * self.marginal.pdf(row)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn samples_follow_the_function() {
+        let dist = Distribution2D::new(&[0.0, 1.0, 3.0, 0.0], 2);
+        for u in [0.1, 0.4, 0.7, 0.95] {
+            let (p, pdf) = dist.sample(&vec2::new(u, u));
+            // the zero cells can never be picked and the pdf agrees with the lookup
+            assert!(pdf > 0.0);
+            assert!((dist.pdf(&p) - pdf).abs() < 1e-5);
+        }
+        // total is 4 so the bottom right cell has density 1 / 4 * 4 cells
+        assert!((dist.pdf(&vec2::new(0.75, 0.25)) - 1.0).abs() < 1e-5);
+        assert!((dist.pdf(&vec2::new(0.25, 0.75)) - 3.0).abs() < 1e-5);
+    }
+}
@@ -29,3 +29,15 @@ pub use ray::*;
 
 mod onb;
 pub use onb::*;
+
+mod distribution;
+pub use distribution::*;
+
+mod quaternion;
+pub use quaternion::*;
+
+mod polynomial;
+pub use polynomial::*;
+
+mod noise;
+pub use noise::*;
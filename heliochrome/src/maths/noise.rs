@@ -0,0 +1,76 @@
+use super::{lerp, vec3};
+
+// largest magnitude `gradient_noise` can reach
+pub const NOISE_MAX: f32 = 1.04;
+// the steepest slope `gradient_noise` can have, used to keep displaced distances conservative
+pub const NOISE_LIPSCHITZ: f32 = 3.0;
+
+// the twelve edge directions of Perlin's improved noise
+const GRADIENTS: [vec3; 12] = [
+    vec3::new(1.0, 1.0, 0.0),
+    vec3::new(-1.0, 1.0, 0.0),
+    vec3::new(1.0, -1.0, 0.0),
+    vec3::new(-1.0, -1.0, 0.0),
+    vec3::new(1.0, 0.0, 1.0),
+    vec3::new(-1.0, 0.0, 1.0),
+    vec3::new(1.0, 0.0, -1.0),
+    vec3::new(-1.0, 0.0, -1.0),
+    vec3::new(0.0, 1.0, 1.0),
+    vec3::new(0.0, -1.0, 1.0),
+    vec3::new(0.0, 1.0, -1.0),
+    vec3::new(0.0, -1.0, -1.0),
+];
+
+fn hash(x: i32, y: i32, z: i32) -> u32 {
+    let mut h = (x as u32).wrapping_mul(0x8da6_b343)
+        ^ (y as u32).wrapping_mul(0xd816_3841)
+        ^ (z as u32).wrapping_mul(0xcb1a_b31f);
+    h ^= h >> 13;
+    h = h.wrapping_mul(0x5bd1_e995);
+    h ^ (h >> 15)
+}
+
+fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+// perlin style gradient noise in roughly [-1, 1], zero on every integer lattice point
+pub fn gradient_noise(p: vec3) -> f32 {
+    let cell = p.floor();
+    let f = p - cell;
+    let (x, y, z) = (cell.x as i32, cell.y as i32, cell.z as i32);
+
+    let corner = |dx: i32, dy: i32, dz: i32| {
+        let g = GRADIENTS[(hash(x + dx, y + dy, z + dz) % 12) as usize];
+        g.dot(f - vec3::new(dx as f32, dy as f32, dz as f32))
+    };
+
+    let (u, v, w) = (fade(f.x), fade(f.y), fade(f.z));
+    lerp(
+        lerp(
+            lerp(corner(0, 0, 0), corner(1, 0, 0), u),
+            lerp(corner(0, 1, 0), corner(1, 1, 0), u),
+            v,
+        ),
+        lerp(
+            lerp(corner(0, 0, 1), corner(1, 0, 1), u),
+            lerp(corner(0, 1, 1), corner(1, 1, 1), u),
+            v,
+        ),
+        w,
+    )
+}
+
+// fractal brownian motion, each octave has double the frequency and half the amplitude
+pub fn fbm(p: vec3, octaves: u32) -> f32 {
+    let mut sum = 0.0;
+    let mut amplitude = 1.0;
+    let mut frequency = 1.0;
+    for _ in 0..octaves {
+        sum += amplitude * gradient_noise(p * frequency);
+        amplitude *= 0.5;
+        frequency *= 2.0;
+    }
+
+    sum
+}
@@ -1,5 +1,6 @@
 use super::vec3;
 
+#[derive(Clone, Copy, Debug)]
 pub struct ONB {
     pub u: vec3,
     pub v: vec3,
@@ -25,4 +26,9 @@ impl ONB {
     pub fn local(&self, a: &vec3) -> vec3 {
         a.x * self.u + a.y * self.v + a.z * self.w
     }
+
+    // the inverse of `local`, expresses a world space vector in this basis
+    pub fn to_local(&self, a: &vec3) -> vec3 {
+        vec3::new(a.dot(self.u), a.dot(self.v), a.dot(self.w))
+    }
 }
@@ -0,0 +1,117 @@
+// real roots of a x^2 + b x + c, smallest first
+pub fn solve_quadratic(a: f32, b: f32, c: f32) -> Option<(f32, f32)> {
+    if a.abs() < 1e-12 {
+        if b.abs() < 1e-12 {
+            return None;
+        }
+        let t = -c / b;
+        return Some((t, t));
+    }
+
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+
+    // avoids cancellation when b is close to the square root
+    let q = -0.5 * (b + b.signum() * discriminant.sqrt());
+    let (t0, t1) = if q == 0.0 { (0.0, 0.0) } else { (q / a, c / q) };
+    Some((t0.min(t1), t0.max(t1)))
+}
+
+fn solve_quadratic_f64(b: f64, c: f64, roots: &mut Vec<f64>) {
+    let discriminant = b * b - 4.0 * c;
+    if discriminant >= 0.0 {
+        let s = discriminant.sqrt();
+        roots.push((-b - s) * 0.5);
+        roots.push((-b + s) * 0.5);
+    }
+}
+
+// largest real root of x^3 + a x^2 + b x + c
+fn largest_cubic_root(a: f64, b: f64, c: f64) -> f64 {
+    let q = (a * a - 3.0 * b) / 9.0;
+    let r = (2.0 * a * a * a - 9.0 * a * b + 27.0 * c) / 54.0;
+    if r * r < q * q * q {
+        let theta = (r / (q * q * q).sqrt()).clamp(-1.0, 1.0).acos();
+        // the roots are -2 sqrt(q) cos((theta + k tau) / 3), k = 1 has the most negative cosine
+        -2.0 * q.sqrt() * ((theta + std::f64::consts::TAU) / 3.0).cos() - a / 3.0
+    } else {
+        let big_a = -r.signum() * (r.abs() + (r * r - q * q * q).sqrt()).cbrt();
+        let big_b = if big_a == 0.0 { 0.0 } else { q / big_a };
+        big_a + big_b - a / 3.0
+    }
+}
+
+// real roots of x^4 + a x^3 + b x^2 + c x + d using ferrari's method, each polished with a
+// couple of newton steps since the resolvent loses a lot of precision.
+pub fn solve_quartic(a: f64, b: f64, c: f64, d: f64) -> Vec<f64> {
+    // substituting x = y - a / 4 gives y^4 + p y^2 + q y + r
+    let a2 = a * a;
+    let p = b - 3.0 * a2 / 8.0;
+    let q = c - a * b / 2.0 + a2 * a / 8.0;
+    let r = d - a * c / 4.0 + a2 * b / 16.0 - 3.0 * a2 * a2 / 256.0;
+
+    let mut roots = Vec::with_capacity(4);
+    if q.abs() < 1e-12 {
+        // biquadratic
+        let mut squares = vec![];
+        solve_quadratic_f64(p, r, &mut squares);
+        for y2 in squares {
+            if y2 >= 0.0 {
+                roots.push(y2.sqrt());
+                roots.push(-y2.sqrt());
+            }
+        }
+    } else {
+        let m = largest_cubic_root(p, p * p / 4.0 - r, -q * q / 8.0);
+        if m <= 0.0 {
+            return vec![];
+        }
+        let s = (2.0 * m).sqrt();
+        solve_quadratic_f64(s, p / 2.0 + m - q / (2.0 * s), &mut roots);
+        solve_quadratic_f64(-s, p / 2.0 + m + q / (2.0 * s), &mut roots);
+    }
+
+    roots
+        .into_iter()
+        .map(|y| {
+            let mut x = y - a / 4.0;
+            for _ in 0..2 {
+                let f = (((x + a) * x + b) * x + c) * x + d;
+                let df = ((4.0 * x + 3.0 * a) * x + 2.0 * b) * x + c;
+                if df != 0.0 {
+                    x -= f / df;
+                }
+            }
+            x
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn quartic_finds_all_real_roots() {
+        // (x - 1)(x - 2)(x + 3)(x - 0.5)
+        let mut roots = solve_quartic(-0.5, -7.0, 9.5, -3.0);
+        roots.sort_by(|a, b| a.total_cmp(b));
+        let expected = [-3.0, 0.5, 1.0, 2.0];
+        assert_eq!(roots.len(), 4);
+        for (root, expected) in roots.iter().zip(expected) {
+            assert!((root - expected).abs() < 1e-9, "{roots:?}");
+        }
+
+        // x^4 + 1 has no real roots
+        assert!(solve_quartic(0.0, 0.0, 0.0, 1.0).is_empty());
+    }
+
+    #[test]
+    fn resolvent_cubic_picks_the_largest_root() {
+        // (x + 1)(x - 2)(x - 5)
+        let root = largest_cubic_root(-6.0, 3.0, 10.0);
+        assert!((root - 5.0).abs() < 1e-9, "{root}");
+    }
+}
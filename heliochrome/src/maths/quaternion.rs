@@ -0,0 +1,207 @@
+use std::{ops::Mul, str::FromStr};
+
+use super::{mat4, vec3, vec4};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct quat {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub w: f32,
+}
+
+impl quat {
+    pub const fn new(x: f32, y: f32, z: f32, w: f32) -> Self {
+        Self { x, y, z, w }
+    }
+
+    pub const fn identity() -> Self {
+        Self::new(0.0, 0.0, 0.0, 1.0)
+    }
+
+    // `angle` in radians
+    pub fn from_axis_angle(axis: vec3, angle: f32) -> Self {
+        let axis = axis.normalized();
+        let (sin, cos) = (angle * 0.5).sin_cos();
+        Self::new(axis.x * sin, axis.y * sin, axis.z * sin, cos)
+    }
+
+    // euler angles in radians, in the same convention as mat4::rotate so a `rotate` key means
+    // the same everywhere: z turns around the x axis, then y around y, then x around z
+    pub fn from_euler(angles: vec3) -> Self {
+        Self::from_axis_angle(vec3::unit_z(), angles.x)
+            * Self::from_axis_angle(vec3::unit_y(), angles.y)
+            * Self::from_axis_angle(vec3::unit_x(), angles.z)
+    }
+
+    pub fn dot(&self, other: quat) -> f32 {
+        self.x * other.x + self.y * other.y + self.z * other.z + self.w * other.w
+    }
+
+    pub fn normalized(&self) -> Self {
+        let mag = self.dot(*self).sqrt();
+        Self::new(self.x / mag, self.y / mag, self.z / mag, self.w / mag)
+    }
+
+    pub fn conjugate(&self) -> Self {
+        Self::new(-self.x, -self.y, -self.z, self.w)
+    }
+
+    // the rotation part of `m`, which should not contain any scaling
+    pub fn from_rotation_matrix(m: &mat4) -> Self {
+        // m[col][row]
+        let (m00, m11, m22) = (m[0][0], m[1][1], m[2][2]);
+        let trace = m00 + m11 + m22;
+        if trace > 0.0 {
+            let s = (trace + 1.0).sqrt() * 2.0;
+            Self::new(
+                (m[1][2] - m[2][1]) / s,
+                (m[2][0] - m[0][2]) / s,
+                (m[0][1] - m[1][0]) / s,
+                0.25 * s,
+            )
+        } else if m00 > m11 && m00 > m22 {
+            let s = (1.0 + m00 - m11 - m22).sqrt() * 2.0;
+            Self::new(
+                0.25 * s,
+                (m[1][0] + m[0][1]) / s,
+                (m[2][0] + m[0][2]) / s,
+                (m[1][2] - m[2][1]) / s,
+            )
+        } else if m11 > m22 {
+            let s = (1.0 + m11 - m00 - m22).sqrt() * 2.0;
+            Self::new(
+                (m[1][0] + m[0][1]) / s,
+                0.25 * s,
+                (m[2][1] + m[1][2]) / s,
+                (m[2][0] - m[0][2]) / s,
+            )
+        } else {
+            let s = (1.0 + m22 - m00 - m11).sqrt() * 2.0;
+            Self::new(
+                (m[2][0] + m[0][2]) / s,
+                (m[2][1] + m[1][2]) / s,
+                0.25 * s,
+                (m[0][1] - m[1][0]) / s,
+            )
+        }
+        .normalized()
+    }
+
+    pub fn to_mat4(&self) -> mat4 {
+        let (x, y, z, w) = (self.x, self.y, self.z, self.w);
+        mat4::new([
+            vec4::new(
+                1.0 - 2.0 * (y * y + z * z),
+                2.0 * (x * y + w * z),
+                2.0 * (x * z - w * y),
+                0.0,
+            ),
+            vec4::new(
+                2.0 * (x * y - w * z),
+                1.0 - 2.0 * (x * x + z * z),
+                2.0 * (y * z + w * x),
+                0.0,
+            ),
+            vec4::new(
+                2.0 * (x * z + w * y),
+                2.0 * (y * z - w * x),
+                1.0 - 2.0 * (x * x + y * y),
+                0.0,
+            ),
+            vec4::new(0.0, 0.0, 0.0, 1.0),
+        ])
+    }
+
+    // spherical interpolation along the shortest arc
+    pub fn slerp(&self, other: quat, t: f32) -> Self {
+        let mut cos = self.dot(other);
+        let mut other = other;
+        if cos < 0.0 {
+            cos = -cos;
+            other = quat::new(-other.x, -other.y, -other.z, -other.w);
+        }
+
+        let (a, b) = if cos > 0.9995 {
+            // close enough that a straight line is fine and avoids dividing by ~0
+            (1.0 - t, t)
+        } else {
+            let theta = cos.acos();
+            let sin = theta.sin();
+            (((1.0 - t) * theta).sin() / sin, (t * theta).sin() / sin)
+        };
+
+        quat::new(
+            self.x * a + other.x * b,
+            self.y * a + other.y * b,
+            self.z * a + other.z * b,
+            self.w * a + other.w * b,
+        )
+        .normalized()
+    }
+
+    pub fn rotate(&self, v: vec3) -> vec3 {
+        let u = vec3::new(self.x, self.y, self.z);
+        let t = 2.0 * u.cross(v);
+        v + self.w * t + u.cross(t)
+    }
+}
+
+impl Mul for quat {
+    type Output = quat;
+    fn mul(self, rhs: quat) -> quat {
+        quat::new(
+            self.w * rhs.x + self.x * rhs.w + self.y * rhs.z - self.z * rhs.y,
+            self.w * rhs.y - self.x * rhs.z + self.y * rhs.w + self.z * rhs.x,
+            self.w * rhs.z + self.x * rhs.y - self.y * rhs.x + self.z * rhs.w,
+            self.w * rhs.w - self.x * rhs.x - self.y * rhs.y - self.z * rhs.z,
+        )
+    }
+}
+
+impl FromStr for quat {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let iter = s.split(',').collect::<Vec<_>>();
+        if iter.len() == 4 {
+            Ok(quat::new(
+                iter[0].trim().parse().map_err(|x| format!("{x}"))?,
+                iter[1].trim().parse().map_err(|x| format!("{x}"))?,
+                iter[2].trim().parse().map_err(|x| format!("{x}"))?,
+                iter[3].trim().parse().map_err(|x| format!("{x}"))?,
+            )
+            .normalized())
+        } else {
+            Err("invalid quat string, unexpected number of components".to_owned())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rotates_like_the_axis_angle() {
+        let q = quat::from_axis_angle(vec3::unit_y(), std::f32::consts::FRAC_PI_2);
+        let v = q.rotate(vec3::unit_x());
+        assert!((v - vec3::new(0.0, 0.0, -1.0)).mag() < 1e-5, "{v:?}");
+
+        // composing with the inverse gets back to where we started
+        let back = (q.conjugate() * q).rotate(vec3::new(1.0, 2.0, 3.0));
+        assert!((back - vec3::new(1.0, 2.0, 3.0)).mag() < 1e-5, "{back:?}");
+    }
+
+    #[test]
+    fn matrix_round_trip() {
+        let m = mat4::rotate_deg(vec3::new(30.0, -120.0, 75.0));
+        let q = quat::from_rotation_matrix(&m);
+        let v = vec3::new(0.3, -1.0, 2.0);
+        let expected = (m * vec4::from_vec3(v, 0.0)).to_vec3();
+        assert!((q.rotate(v) - expected).mag() < 1e-4);
+        assert!(((q.to_mat4() * vec4::from_vec3(v, 0.0)).to_vec3() - expected).mag() < 1e-4);
+
+        let q = quat::from_euler(vec3::new(30.0, -120.0, 75.0).to_rad());
+        assert!((q.rotate(v) - expected).mag() < 1e-4);
+    }
+}
@@ -4,11 +4,25 @@ use crate::maths::vec3;
 pub struct Ray {
     pub origin: vec3,
     pub direction: vec3,
+    // when within the camera's shutter interval the ray was cast, for motion blur
+    pub time: f32,
 }
 
 impl Ray {
     pub fn new(origin: vec3, direction: vec3) -> Self {
-        Ray { origin, direction }
+        Ray {
+            origin,
+            direction,
+            time: 0.0,
+        }
+    }
+
+    pub fn new_at_time(origin: vec3, direction: vec3, time: f32) -> Self {
+        Ray {
+            origin,
+            direction,
+            time,
+        }
     }
 
     pub fn at(&self, t: f32) -> vec3 {
@@ -195,3 +195,18 @@ pub(crate) use vec2_impl;
 
 vec2_impl!(vec2, f32, x, y);
 vec2_impl!(dvec2, f64, x, y);
+
+impl std::str::FromStr for vec2 {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let iter = s.split(',').collect::<Vec<_>>();
+        if iter.len() == 2 {
+            Ok(vec2::new(
+                iter[0].trim().parse().map_err(|x| format!("{x}"))?,
+                iter[1].trim().parse().map_err(|x| format!("{x}"))?,
+            ))
+        } else {
+            Err("invalid vec2 string, unexpected number of components".to_owned())
+        }
+    }
+}
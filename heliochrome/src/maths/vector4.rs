@@ -268,3 +268,20 @@ macro_rules! vec4_impl {
 pub(crate) use vec4_impl;
 
 vec4_impl!(vec4, f32, x, y, z, w, vec3);
+
+impl std::str::FromStr for vec4 {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let iter = s.split(',').collect::<Vec<_>>();
+        if iter.len() == 4 {
+            Ok(vec4::new(
+                iter[0].trim().parse().map_err(|x| format!("{x}"))?,
+                iter[1].trim().parse().map_err(|x| format!("{x}"))?,
+                iter[2].trim().parse().map_err(|x| format!("{x}"))?,
+                iter[3].trim().parse().map_err(|x| format!("{x}"))?,
+            ))
+        } else {
+            Err("invalid vec4 string, unexpected number of components".to_owned())
+        }
+    }
+}
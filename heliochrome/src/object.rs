@@ -3,12 +3,14 @@ use std::error::Error;
 use crate::{
     hittables::{BounceInfo, Hittable, HittableObject, Intersection, AABB},
     loader::{collect_until_next_item, FromHCY},
-    materials::Material,
+    materials::{Material, Scatterable},
     maths::vec3,
     maths::Ray,
     transform::Transform,
 };
 
+const CUTOUT_EPSILON: f32 = 0.0001;
+
 #[derive(Clone, Debug)]
 pub struct Object {
     pub hittable: HittableObject,
@@ -32,24 +34,40 @@ impl Object {
 
 impl Hittable for Object {
     fn intersect(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<Intersection> {
-        let r = if let Some(transform) = &self.transform {
+        let transform = self.transform.as_ref().map(|t| t.at(ray.time));
+        let r = if let Some(transform) = &transform {
             transform.trans_ray(ray)
         } else {
             *ray
         };
 
-        self.hittable.intersect(&r, t_min, t_max)
+        let mut t_min = t_min;
+        loop {
+            let intersection = self.hittable.intersect(&r, t_min, t_max)?;
+            if let Some(opacity) = self.material.opacity() {
+                // cut out surfaces are skipped here so every ray, including the ones the
+                // light pdfs cast, keeps looking for the next surface behind them.
+                let bounce_info = self.hittable.get_bounce_info(&r, intersection);
+                if opacity.cuts_out(&bounce_info) {
+                    t_min = intersection.t + CUTOUT_EPSILON;
+                    continue;
+                }
+            }
+
+            return Some(intersection);
+        }
     }
 
     fn get_bounce_info(&self, ray: &Ray, intersection: Intersection) -> BounceInfo {
-        let r = if let Some(transform) = &self.transform {
+        let transform = self.transform.as_ref().map(|t| t.at(ray.time));
+        let r = if let Some(transform) = &transform {
             transform.trans_ray(ray)
         } else {
             *ray
         };
 
         let mut bounce_info = self.hittable.get_bounce_info(&r, intersection);
-        if let Some(transform) = &self.transform {
+        if let Some(transform) = &transform {
             bounce_info.p = ray.at(bounce_info.t);
             bounce_info.set_normal(ray, transform.trans_normal(&bounce_info.normal));
         }
@@ -66,6 +84,11 @@ impl Hittable for Object {
         aabb
     }
 
+    fn can_sample(&self) -> bool {
+        self.hittable.can_sample()
+    }
+
+    // the light pdfs have no time so moving emitters are sampled in their first pose
     fn pdf_value(&self, origin: &vec3, dir: &vec3) -> f32 {
         if let Some(transform) = &self.transform {
             let origin = &transform.trans_pos(origin);
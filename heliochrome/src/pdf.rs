@@ -2,6 +2,7 @@ use enum_dispatch::enum_dispatch;
 use rand::seq::SliceRandom;
 
 use crate::{
+    environment::Environment,
     hittables::Hittable,
     maths::{vec3, ONB},
     object::Object,
@@ -38,6 +39,39 @@ impl ProbabilityDensityFn for CosinePdf {
         self.onb.local(&vec3::random_cosine_direction())
     }
 }
+// uniform over the directions within a cone around `w`
+pub struct ConePdf {
+    onb: ONB,
+    cos_max: f32,
+}
+
+impl ConePdf {
+    pub fn new(w: vec3, cos_max: f32) -> Self {
+        Self {
+            onb: ONB::new_from_w(w),
+            // a cone with no width would have an infinite density
+            cos_max: cos_max.min(1.0 - f32::EPSILON),
+        }
+    }
+}
+
+impl ProbabilityDensityFn for ConePdf {
+    fn value(&self, dir: &vec3) -> f32 {
+        if dir.normalized().dot(self.onb.w) >= self.cos_max {
+            1.0 / (std::f32::consts::TAU * (1.0 - self.cos_max))
+        } else {
+            0.0
+        }
+    }
+
+    fn generate(&self) -> vec3 {
+        let z = 1.0 - rand::random::<f32>() * (1.0 - self.cos_max);
+        let phi = std::f32::consts::TAU * rand::random::<f32>();
+        let r = (1.0 - z * z).max(0.0).sqrt();
+        self.onb.local(&vec3::new(phi.cos() * r, phi.sin() * r, z))
+    }
+}
+
 pub struct ObjectPdf<'a> {
     pub obj: &'a Object,
     pub origin: vec3,
@@ -59,18 +93,18 @@ impl<'a> ProbabilityDensityFn for ObjectPdf<'a> {
     }
 }
 
-pub struct ObjectListPdf {
-    pub objs: Vec<Object>,
+pub struct ObjectListPdf<'a> {
+    pub objs: Vec<&'a Object>,
     pub origin: vec3,
 }
 
-impl ObjectListPdf {
-    pub fn new(objs: Vec<Object>, origin: vec3) -> Self {
+impl<'a> ObjectListPdf<'a> {
+    pub fn new(objs: Vec<&'a Object>, origin: vec3) -> Self {
         Self { objs, origin }
     }
 }
 
-impl ProbabilityDensityFn for ObjectListPdf {
+impl<'a> ProbabilityDensityFn for ObjectListPdf<'a> {
     fn value(&self, dir: &vec3) -> f32 {
         self.objs
             .iter()
@@ -86,11 +120,33 @@ impl ProbabilityDensityFn for ObjectListPdf {
     }
 }
 
+pub struct EnvironmentPdf<'a> {
+    pub env: &'a Environment,
+}
+
+impl<'a> EnvironmentPdf<'a> {
+    pub fn new(env: &'a Environment) -> Self {
+        Self { env }
+    }
+}
+
+impl<'a> ProbabilityDensityFn for EnvironmentPdf<'a> {
+    fn value(&self, dir: &vec3) -> f32 {
+        self.env.pdf_value(dir)
+    }
+
+    fn generate(&self) -> vec3 {
+        self.env.generate()
+    }
+}
+
 #[enum_dispatch(ProbabilityDensityFn)]
 pub enum Pdf<'a> {
     CosinePdf,
+    ConePdf,
     ObjectPdf(ObjectPdf<'a>),
-    ObjectListPdf,
+    ObjectListPdf(ObjectListPdf<'a>),
+    EnvironmentPdf(EnvironmentPdf<'a>),
 }
 
 impl<P: ProbabilityDensityFn> ProbabilityDensityFn for Vec<P> {
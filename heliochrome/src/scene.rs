@@ -1,22 +1,34 @@
-use std::{error::Error, path::Path};
+use std::{error::Error, f32::consts::FRAC_PI_2, path::Path};
 
 use crate::{
     accel::{Accel, Accelerator},
     camera::Camera,
     color::Color,
-    hittables::Intersection,
+    environment::Environment,
+    hittables::{Hittable, Intersection},
     image::Image,
-    loader::{collect_until_next_item, parse_into, FromHCY},
+    light::Light,
+    loader::{collect_nested_item, collect_until_next_item, parse_into, FromHCY},
     materials::Scatterable,
     maths::{vec2, vec3, Ray},
     object::Object,
-    pdf::ObjectPdf,
+    pdf::{EnvironmentPdf, ObjectListPdf, Pdf},
+    sky::PhysicalSky,
+    texture::Texture,
 };
 
 #[derive(Debug)]
 pub enum SkyBox {
     Color(Color),
-    Equirectangular(Image),
+    Equirectangular(Environment),
+    Sky(PhysicalSky),
+    Gradient {
+        horizon: Color,
+        zenith: Color,
+        ground: Color,
+    },
+    // any texture wrapped around the sky sphere with equirectangular uvs
+    Texture(Texture),
     Debug,
 }
 
@@ -24,17 +36,40 @@ impl SkyBox {
     pub fn sample(&self, dir: vec3) -> Color {
         match self {
             SkyBox::Color(c) => *c,
-            SkyBox::Equirectangular(img) => {
+            SkyBox::Equirectangular(env) => env.sample(&dir),
+            SkyBox::Sky(sky) => sky.sample(&dir),
+            SkyBox::Gradient {
+                horizon,
+                zenith,
+                ground,
+            } => {
+                let elevation = dir.normalized().y.clamp(-1.0, 1.0).asin() / FRAC_PI_2;
+                if elevation >= 0.0 {
+                    horizon * (1.0 - elevation) + zenith * elevation
+                } else {
+                    horizon * (1.0 + elevation) - ground * elevation
+                }
+            }
+            SkyBox::Texture(texture) => {
+                let dir = dir.normalized();
                 let uv = vec2::new(
                     0.5 + dir.z.atan2(dir.x) / std::f32::consts::TAU,
-                    0.5 + dir.y.asin() / std::f32::consts::PI,
+                    0.5 + dir.y.clamp(-1.0, 1.0).asin() / std::f32::consts::PI,
                 );
-
-                img.sample_uv(&uv)
+                texture.sample(&uv, &dir)
             }
             SkyBox::Debug => ((dir.normalized() + vec3::splat(1.0)) * 0.5).into(),
         }
     }
+
+    // the skybox as a light source, if it is worth importance sampling
+    pub fn importance_pdf(&self) -> Option<Pdf<'_>> {
+        match self {
+            SkyBox::Equirectangular(env) => Some(EnvironmentPdf::new(env).into()),
+            SkyBox::Sky(sky) => sky.sun_pdf().map(Pdf::from),
+            _ => None,
+        }
+    }
 }
 
 impl FromHCY for SkyBox {
@@ -60,23 +95,76 @@ impl FromHCY for SkyBox {
                     Ok(SkyBox::Color(color))
                 }
                 "debug" => Ok(SkyBox::Debug),
+                "sky" => Ok(SkyBox::Sky(PhysicalSky::from_hcy(None, lines)?)),
+                "gradient" => {
+                    let mut horizon = None;
+                    let mut zenith = None;
+                    let mut ground = None;
+                    for line in lines.iter() {
+                        let (key, value) = line
+                            .split_once(':')
+                            .ok_or("invalid key value pair syntax")?;
+                        match key.trim() {
+                            "horizon" => horizon = Some(parse_into::<Color>(value)?),
+                            "zenith" => zenith = Some(parse_into(value)?),
+                            "ground" => ground = Some(parse_into(value)?),
+                            _ => {}
+                        }
+                    }
+
+                    let horizon = horizon.ok_or("missing required key `horizon`")?;
+                    Ok(SkyBox::Gradient {
+                        horizon,
+                        zenith: zenith.ok_or("missing required key `zenith`")?,
+                        ground: ground.unwrap_or(horizon),
+                    })
+                }
+                "texture" => {
+                    let mut texture = None;
+                    let mut line_iter = lines.iter();
+                    while let Some(line) = line_iter.next() {
+                        let (key, value) = line
+                            .split_once(':')
+                            .ok_or("invalid key value pair syntax")?;
+                        if key.trim() == "texture" {
+                            texture = Some(
+                                Texture::from_hcy(
+                                    Some(value),
+                                    collect_nested_item(line, &mut line_iter),
+                                )
+                                .map_err(|err| format!("could not parse texture key: {err}"))?,
+                            );
+                        }
+                    }
+                    Ok(SkyBox::Texture(
+                        texture.ok_or("missing required key `texture`")?,
+                    ))
+                }
                 "hdri" => {
-                    let path = lines.iter().fold(
-                        Err(Box::<dyn Error>::from("invalid path syntax")),
-                        |a, x| {
-                            if a.is_err() {
-                                let split = x.trim().split_once("path: ");
-                                if let Some((_, path)) = split {
-                                    Ok(Path::new(path))
-                                } else {
-                                    a
-                                }
-                            } else {
-                                a
-                            }
-                        },
-                    )?;
-                    Ok(SkyBox::Equirectangular(Image::load_from_hdri(path)?))
+                    let mut path = None;
+                    let mut rotation = 0.0;
+                    let mut intensity = 1.0;
+                    let mut exposure = 0.0;
+                    for line in lines.iter() {
+                        let (key, value) = line
+                            .split_once(':')
+                            .ok_or("invalid key value pair syntax")?;
+                        match key.trim() {
+                            "path" => path = Some(Path::new(value.trim())),
+                            "rotation" => rotation = parse_into(value)?,
+                            "intensity" => intensity = parse_into(value)?,
+                            "exposure" => exposure = parse_into(value)?,
+                            _ => {}
+                        }
+                    }
+
+                    let mut env = Environment::new(Image::load_from_hdri(
+                        path.ok_or("missing required key `path`")?,
+                    )?);
+                    env.rotation = rotation;
+                    env.intensity = intensity;
+                    env.exposure = exposure;
+                    Ok(SkyBox::Equirectangular(env))
                 }
                 _ => Err(format!("unknown member {member}"))?,
             }
@@ -93,48 +181,56 @@ pub struct Scene {
 
     pub objects: Accel<Object>,
     pub important_indices: Vec<usize>,
+    pub lights: Vec<Light>,
 }
 
 impl Scene {
-    pub fn new(camera: Camera, skybox: SkyBox, objects: Vec<Object>) -> Self {
+    pub fn new(
+        camera: Camera,
+        skybox: SkyBox,
+        objects: Vec<Object>,
+    ) -> Result<Self, Box<dyn Error>> {
         let objects = Accel::new(objects);
-        let important_indices = objects
-            .hittables
-            .iter()
-            .enumerate()
-            .filter_map(|(idx, obj)| {
-                if !obj.material.is_important() {
-                    None
-                } else {
-                    Some(idx)
-                }
-            })
-            .collect::<Vec<_>>();
+        let mut important_indices = vec![];
+        for (idx, obj) in objects.hittables.iter().enumerate() {
+            if !obj.material.is_important() {
+                continue;
+            }
+            // an emitter that can't be sampled would crash the integrator later on
+            if !obj.can_sample() {
+                Err(format!(
+                    "object {idx} is emissive but its primitive can't be sampled as a light"
+                ))?;
+            }
+            important_indices.push(idx);
+        }
 
-        Self {
+        Ok(Self {
             camera,
             skybox,
             objects,
             important_indices,
-        }
+            lights: vec![],
+        })
     }
 
     pub fn intersect(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<(Intersection, &Object)> {
         self.objects.intersect_obj(ray, t_min, t_max)
     }
 
-    pub fn make_importance_pdf(&self, origin: &vec3) -> Vec<ObjectPdf> {
-        self.important_indices
-            .iter()
-            .map(|idx| ObjectPdf::new(self.objects.get_nth(*idx), *origin))
-            .collect()
-    }
+    // picks one of the important objects uniformly, none if there are none
+    pub fn make_importance_pdf(&self, origin: &vec3) -> Option<ObjectListPdf> {
+        if self.important_indices.is_empty() {
+            return None;
+        }
 
-    pub fn get_importants(&self) -> Vec<Object> {
-        self.important_indices
-            .iter()
-            .map(|idx| self.objects.get_nth(*idx).clone())
-            .collect()
+        Some(ObjectListPdf::new(
+            self.important_indices
+                .iter()
+                .map(|idx| self.objects.get_nth(*idx))
+                .collect(),
+            *origin,
+        ))
     }
 }
 
@@ -143,6 +239,7 @@ impl FromHCY for Scene {
         let mut camera = None;
         let mut skybox = None;
         let mut objects = None;
+        let mut lights = vec![];
 
         let mut line_iter = lines.iter();
         while let Some(line) = line_iter.next() {
@@ -169,18 +266,40 @@ impl FromHCY for Scene {
                     while let Some(line) = line_iter.next() {
                         let lines = collect_until_next_item(&mut line_iter);
                         if !lines.is_empty() {
-                            objs.push(Object::from_hcy(Some(line), lines)?);
+                            let obj = Object::from_hcy(Some(line), lines)?;
+                            if obj.material.is_important() && !obj.can_sample() {
+                                Err(format!(
+                                    "object `{}` is emissive but its primitive can't be sampled as a light",
+                                    line.trim().trim_end_matches(':')
+                                ))?;
+                            }
+                            objs.push(obj);
                         }
                     }
                     objects = Some(objs);
                 }
+                "lights" => {
+                    let lines = collect_until_next_item(&mut line_iter);
+                    let mut line_iter = lines.iter();
+                    while let Some(line) = line_iter.next() {
+                        let (_, member) = line
+                            .split_once(':')
+                            .ok_or("invalid key value pair syntax")?;
+                        lights.push(
+                            Light::from_hcy(Some(member), collect_until_next_item(&mut line_iter))
+                                .map_err(|err| format!("could not parse light: {err}"))?,
+                        );
+                    }
+                }
                 _ => {}
             }
         }
-        Ok(Scene::new(
+        let mut scene = Scene::new(
             camera.ok_or("Missing required key `camera`")?,
             skybox.ok_or("Missing required key `skybox`")?,
             objects.ok_or("Missing required key `objects`")?,
-        ))
+        )?;
+        scene.lights = lights;
+        Ok(scene)
     }
 }
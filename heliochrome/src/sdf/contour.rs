@@ -0,0 +1,206 @@
+use crate::{
+    hittables::AABB,
+    load_obj::RawMesh,
+    maths::{mat3, vec3},
+    sdf::SDF,
+};
+
+// pulls vertices towards the average of their crossings where the planes don't pin them down
+const MASS_POINT_WEIGHT: f32 = 0.05;
+
+// the edges of a cell as offsets of their two corners
+const CELL_EDGES: [([usize; 3], [usize; 3]); 12] = [
+    ([0, 0, 0], [1, 0, 0]),
+    ([0, 1, 0], [1, 1, 0]),
+    ([0, 0, 1], [1, 0, 1]),
+    ([0, 1, 1], [1, 1, 1]),
+    ([0, 0, 0], [0, 1, 0]),
+    ([1, 0, 0], [1, 1, 0]),
+    ([0, 0, 1], [0, 1, 1]),
+    ([1, 0, 1], [1, 1, 1]),
+    ([0, 0, 0], [0, 0, 1]),
+    ([1, 0, 0], [1, 0, 1]),
+    ([0, 1, 0], [0, 1, 1]),
+    ([1, 1, 0], [1, 1, 1]),
+];
+
+// extracts the surface of an sdf inside `bounds` as a triangle mesh by dual contouring
+// (Ju et al. 2002) on a grid with `resolution` cells along every axis. each cell the surface
+// passes through gets one vertex where the tangent planes at its edge crossings meet, which
+// keeps the sharp edges and corners marching cubes would bevel.
+pub fn dual_contour(sdf: &dyn SDF, bounds: &AABB, resolution: usize) -> RawMesh {
+    let n = resolution.max(1);
+    let step = (bounds.max - bounds.min) / n as f32;
+    let point =
+        |i: usize, j: usize, k: usize| bounds.min + vec3::new(i as f32, j as f32, k as f32) * step;
+
+    let point_index = |i: usize, j: usize, k: usize| i + (n + 1) * (j + (n + 1) * k);
+    let mut samples = Vec::with_capacity((n + 1).pow(3));
+    for k in 0..=n {
+        for j in 0..=n {
+            for i in 0..=n {
+                samples.push(sdf.dist(point(i, j, k)));
+            }
+        }
+    }
+
+    let cell_index = |i: usize, j: usize, k: usize| i + n * (j + n * k);
+    let mut cell_vertices = vec![None; n * n * n];
+    let mut vertices = vec![];
+    let mut normals = vec![];
+    for k in 0..n {
+        for j in 0..n {
+            for i in 0..n {
+                let mut crossings = vec![];
+                for (a, b) in CELL_EDGES {
+                    let pa = [i + a[0], j + a[1], k + a[2]];
+                    let pb = [i + b[0], j + b[1], k + b[2]];
+                    let da = samples[point_index(pa[0], pa[1], pa[2])];
+                    let db = samples[point_index(pb[0], pb[1], pb[2])];
+                    if (da < 0.0) != (db < 0.0) {
+                        let t = da / (da - db);
+                        let pa = point(pa[0], pa[1], pa[2]);
+                        let pb = point(pb[0], pb[1], pb[2]);
+                        let p = pa + (pb - pa) * t;
+                        crossings.push((p, sdf.normal_at(&p)));
+                    }
+                }
+                if crossings.is_empty() {
+                    continue;
+                }
+
+                let min = point(i, j, k);
+                let vertex = solve_qef(&crossings).max(&min).min(&(min + step));
+                cell_vertices[cell_index(i, j, k)] = Some(vertices.len() as u32);
+                vertices.push(vertex);
+                normals.push(sdf.normal_at(&vertex));
+            }
+        }
+    }
+
+    // every grid edge the surface crosses becomes a quad between the four cells around it,
+    // wound so it faces away from the inside
+    let mut indices = vec![];
+    for k in 0..=n {
+        for j in 0..=n {
+            for i in 0..=n {
+                let p = [i, j, k];
+                let d0 = samples[point_index(i, j, k)];
+                for axis in 0..3 {
+                    let (u, v) = ((axis + 1) % 3, (axis + 2) % 3);
+                    if p[axis] >= n || p[u] == 0 || p[u] >= n || p[v] == 0 || p[v] >= n {
+                        continue;
+                    }
+
+                    let mut q = p;
+                    q[axis] += 1;
+                    let d1 = samples[point_index(q[0], q[1], q[2])];
+                    if (d0 < 0.0) == (d1 < 0.0) {
+                        continue;
+                    }
+
+                    let cell = |du: usize, dv: usize| {
+                        let mut c = p;
+                        c[u] -= du;
+                        c[v] -= dv;
+                        cell_vertices[cell_index(c[0], c[1], c[2])]
+                    };
+                    let quad = [cell(1, 1), cell(0, 1), cell(0, 0), cell(1, 0)];
+                    let [Some(a), Some(b), Some(c), Some(d)] = quad else {
+                        continue;
+                    };
+
+                    if d0 < 0.0 {
+                        indices.extend([a, b, c, a, c, d]);
+                    } else {
+                        indices.extend([a, c, b, a, d, c]);
+                    }
+                }
+            }
+        }
+    }
+
+    RawMesh {
+        vertices,
+        normals,
+        texcoords: vec![],
+        indices,
+    }
+}
+
+// the point closest to all the tangent planes in the least squares sense, regularised
+// towards the mass point of the crossings
+fn solve_qef(crossings: &[(vec3, vec3)]) -> vec3 {
+    let mass_point = crossings
+        .iter()
+        .fold(vec3::default(), |acc, (p, _)| acc + p)
+        / crossings.len() as f32;
+
+    let mut ata = [vec3::default(); 3];
+    let mut atb = vec3::default();
+    for (p, normal) in crossings {
+        // solved relative to the mass point to keep the numbers small
+        let d = normal.dot(p - mass_point);
+        for (c, col) in ata.iter_mut().enumerate() {
+            *col += *normal * normal[c];
+        }
+        atb += *normal * d;
+    }
+    for (c, col) in ata.iter_mut().enumerate() {
+        col[c] += MASS_POINT_WEIGHT;
+    }
+
+    mass_point + mat3::new(ata).inverse() * atb
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        hittables::Mesh,
+        sdf::{DistanceGrid, Sphere, Union, AAB},
+    };
+
+    #[test]
+    fn round_trips_through_a_mesh() {
+        let sphere = Sphere::new(1.0, vec3::splat(0.0));
+        let bounds = AABB::new(vec3::splat(-1.5), vec3::splat(1.5));
+        let raw = dual_contour(&sphere, &bounds, 10);
+        assert!(!raw.indices.is_empty());
+        for v in raw.vertices.iter() {
+            assert!(sphere.dist(*v).abs() < 0.03);
+        }
+
+        let mesh = Mesh::new(&raw.vertices, &raw.indices, &raw.normals);
+        let grid = DistanceGrid::from_mesh(&mesh, 16);
+        for p in [
+            vec3::splat(0.0),
+            vec3::new(0.5, 0.0, 0.0),
+            vec3::new(1.0, 1.0, 1.0),
+        ] {
+            assert!((grid.dist(p) - sphere.dist(p)).abs() < 0.1);
+        }
+
+        // outside the grid, next to another shape, the distance must not overshoot
+        let union = Union {
+            a: grid,
+            b: Sphere::new(0.5, vec3::new(4.0, 0.0, 0.0)),
+        };
+        for p in [
+            vec3::new(3.0, 1.2, 0.0),
+            vec3::new(-3.0, 1.2, 0.0),
+            vec3::new(0.0, -2.0, 0.0),
+        ] {
+            let exact = sphere.dist(p).min(union.b.dist(p));
+            assert!(union.dist(p) <= exact + 0.01 && union.dist(p) > 0.0);
+        }
+
+        // the corners of a box come out sharp
+        let cube = AAB::new(vec3::splat(0.5));
+        let raw = dual_contour(&cube, &AABB::new(vec3::splat(-1.0), vec3::splat(1.0)), 7);
+        assert!(raw
+            .vertices
+            .iter()
+            .any(|v| (*v - vec3::splat(0.5)).mag() < 0.01));
+    }
+}
@@ -1,35 +1,38 @@
-use std::fmt::Debug;
+use std::{error::Error, fmt::Debug};
 
-use crate::{hittables::AABB, maths::vec3};
+use crate::{
+    color::Color,
+    hittables::AABB,
+    loader::{collect_nested_item, parse_into, FromHCY},
+    maths::{mat4, vec3},
+};
 
 const NORMAL_H: f32 = 0.0001;
 
+#[allow(unused_variables)] // default trait impls
 pub trait SDF: Send + Sync + Debug {
     fn dist(&self, p: vec3) -> f32;
 
     fn normal_at(&self, p: &vec3) -> vec3 {
-        // let ks = [
-        //     vec3::new(1.0, -1.0, -1.0),
-        //     vec3::new(-1.0, -1.0, 1.0),
-        //     vec3::new(-1.0, 1.0, -1.0),
-        //     vec3::splat(1.0),
-        // ];
-        // ks.into_iter()
-        //     .fold(vec3::splat(0.0), |a, c| {
-        //         a + (c * self.dist(p + c * NORMAL_H))
-        //     })
-        //     .normalize()
-
-        (vec3::new(
-            self.dist(p + vec3::unit_x() * NORMAL_H) - self.dist(p - vec3::unit_x() * NORMAL_H),
-            self.dist(p + vec3::unit_y() * NORMAL_H) - self.dist(p - vec3::unit_y() * NORMAL_H),
-            self.dist(p + vec3::unit_z() * NORMAL_H) - self.dist(p - vec3::unit_z() * NORMAL_H),
-        ))
+        // four samples on the corners of a tetrahedron instead of six central differences
+        [
+            vec3::new(1.0, -1.0, -1.0),
+            vec3::new(-1.0, -1.0, 1.0),
+            vec3::new(-1.0, 1.0, -1.0),
+            vec3::splat(1.0),
+        ]
+        .into_iter()
+        .fold(vec3::splat(0.0), |a, k| a + k * self.dist(p + k * NORMAL_H))
         .normalized()
     }
 
     fn make_bounding_box(&self) -> AABB;
 
+    // an optional colour attribute of the surface near `p`, read by `Texture::Attribute`
+    fn color(&self, p: vec3) -> Option<Color> {
+        None
+    }
+
     fn difference<T: SDF>(self, other: T) -> Difference<Self, T>
     where
         Self: Sized,
@@ -93,9 +96,357 @@ pub trait SDF: Send + Sync + Debug {
             primitive: self,
         }
     }
+
+    fn repeat(self, period: vec3, count: vec3) -> Repeat<Self>
+    where
+        Self: Sized,
+    {
+        Repeat {
+            period,
+            count,
+            primitive: self,
+        }
+    }
+
+    fn mirror(self, axes: [bool; 3]) -> Mirror<Self>
+    where
+        Self: Sized,
+    {
+        Mirror {
+            primitive: self,
+            axes,
+        }
+    }
+
+    // fails for unbounded shapes, see Bend::new
+    fn bend(self, k: f32) -> Result<Bend<Self>, Box<dyn Error>>
+    where
+        Self: Sized,
+    {
+        Bend::new(self, k)
+    }
+
+    fn elongate(self, h: vec3) -> Elongate<Self>
+    where
+        Self: Sized,
+    {
+        Elongate { h, primitive: self }
+    }
+
+    fn round(self, radius: f32) -> Round<Self>
+    where
+        Self: Sized,
+    {
+        Round {
+            radius,
+            primitive: self,
+        }
+    }
+
+    fn onion(self, thickness: f32) -> Onion<Self>
+    where
+        Self: Sized,
+    {
+        Onion {
+            thickness,
+            primitive: self,
+        }
+    }
+
+    fn displace(self, amplitude: f32, frequency: f32, octaves: u32) -> Displace<Self>
+    where
+        Self: Sized,
+    {
+        Displace {
+            amplitude,
+            frequency,
+            octaves,
+            primitive: self,
+        }
+    }
+
+    fn paint(self, color: Color) -> Paint<Self>
+    where
+        Self: Sized,
+    {
+        Paint {
+            color,
+            primitive: self,
+        }
+    }
+
+    fn transformed(self, matrix: mat4) -> Transformed<Self>
+    where
+        Self: Sized,
+    {
+        Transformed::new(self, matrix)
+    }
+
+    fn translate(self, translate: vec3) -> Transformed<Self>
+    where
+        Self: Sized,
+    {
+        Transformed::new(self, mat4::translate(translate))
+    }
+
+    // euler angles in degrees
+    fn rotate(self, rotate: vec3) -> Transformed<Self>
+    where
+        Self: Sized,
+    {
+        Transformed::new(self, mat4::rotate_deg(rotate))
+    }
+
+    fn scale(self, scale: vec3) -> Transformed<Self>
+    where
+        Self: Sized,
+    {
+        Transformed::new(self, mat4::scale(scale))
+    }
 }
 
 mod operations;
 pub use operations::*;
 mod primitives;
 pub use primitives::*;
+mod contour;
+pub use contour::*;
+
+// lets trees built at runtime, like the ones in scene files, be composed with the generic ops
+impl SDF for Box<dyn SDF> {
+    fn dist(&self, p: vec3) -> f32 {
+        self.as_ref().dist(p)
+    }
+
+    fn normal_at(&self, p: &vec3) -> vec3 {
+        self.as_ref().normal_at(p)
+    }
+
+    fn make_bounding_box(&self) -> AABB {
+        self.as_ref().make_bounding_box()
+    }
+
+    fn color(&self, p: vec3) -> Option<Color> {
+        self.as_ref().color(p)
+    }
+}
+
+// colours a fractal by how close the orbit of a point comes to the origin, from `inner` for
+// orbits passing right through it to `outer` for ones staying a unit or more away
+#[derive(Debug, Clone, Copy)]
+pub struct OrbitTrap {
+    pub inner: Color,
+    pub outer: Color,
+}
+
+impl OrbitTrap {
+    pub fn color(&self, trap: f32) -> Color {
+        let t = trap.clamp(0.0, 1.0);
+        self.inner * (1.0 - t) + self.outer * t
+    }
+
+    // from the `inner color` and `outer color` keys of a fractal, which go together
+    pub(crate) fn from_keys(
+        inner: Option<Color>,
+        outer: Option<Color>,
+    ) -> Result<Option<Self>, Box<dyn Error>> {
+        match (inner, outer) {
+            (Some(inner), Some(outer)) => Ok(Some(OrbitTrap { inner, outer })),
+            (None, None) => Ok(None),
+            _ => Err("orbit traps need both an `inner color` and an `outer color`")?,
+        }
+    }
+}
+
+// blends the colours of two shapes with `h` the weight of `a`, a shape without one gives way
+pub(crate) fn mix_colors(a: Option<Color>, b: Option<Color>, h: f32) -> Option<Color> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a * h + b * (1.0 - h)),
+        (a, b) => a.or(b),
+    }
+}
+
+impl FromHCY for Box<dyn SDF> {
+    fn from_hcy(member: Option<&str>, lines: Vec<String>) -> Result<Self, Box<dyn Error>> {
+        let member = member.ok_or("invalid syntax missing sdf type")?.trim();
+        match member {
+            "sphere" => return Ok(Box::new(Sphere::from_hcy(None, lines)?)),
+            "torus" => return Ok(Box::new(Torus::from_hcy(None, lines)?)),
+            "box" => return Ok(Box::new(AAB::from_hcy(None, lines)?)),
+            "mandelbulb" => return Ok(Box::new(MandelBulb::from_hcy(None, lines)?)),
+            "mesh" => return Ok(Box::new(DistanceGrid::from_hcy(None, lines)?)),
+            "rounded box" => return Ok(Box::new(RoundedBox::from_hcy(None, lines)?)),
+            "box frame" => return Ok(Box::new(BoxFrame::from_hcy(None, lines)?)),
+            "capsule" => return Ok(Box::new(Capsule::from_hcy(None, lines)?)),
+            "cylinder" => return Ok(Box::new(Cylinder::from_hcy(None, lines)?)),
+            "cone" => return Ok(Box::new(Cone::from_hcy(None, lines)?)),
+            "plane" => return Ok(Box::new(Plane::from_hcy(None, lines)?)),
+            "hex prism" => return Ok(Box::new(HexPrism::from_hcy(None, lines)?)),
+            "ellipsoid" => return Ok(Box::new(Ellipsoid::from_hcy(None, lines)?)),
+            "octahedron" => return Ok(Box::new(Octahedron::from_hcy(None, lines)?)),
+            "link" => return Ok(Box::new(Link::from_hcy(None, lines)?)),
+            "menger sponge" => return Ok(Box::new(MengerSponge::from_hcy(None, lines)?)),
+            "julia" => return Ok(Box::new(Julia::from_hcy(None, lines)?)),
+            "mandelbox" => return Ok(Box::new(Mandelbox::from_hcy(None, lines)?)),
+            _ => {}
+        }
+
+        // everything else is an operation on one or two nested shapes
+        let mut a: Option<Box<dyn SDF>> = None;
+        let mut b: Option<Box<dyn SDF>> = None;
+        let mut k = None;
+        let mut period = None;
+        let mut count = None;
+        let mut axes = [false; 3];
+        let mut h = None;
+        let mut radius = None;
+        let mut thickness = None;
+        let mut amplitude = None;
+        let mut frequency = 1.0;
+        let mut octaves = 4;
+        let mut color = None;
+        let mut translate = vec3::splat(0.0);
+        let mut rotate = vec3::splat(0.0);
+        let mut scale = vec3::splat(1.0);
+
+        let mut line_iter = lines.iter();
+        while let Some(line) = line_iter.next() {
+            let (key, value) = line
+                .split_once(':')
+                .ok_or("invalid key value pair syntax")?;
+            match key.trim() {
+                "a" | "shape" => {
+                    a = Some(
+                        Self::from_hcy(Some(value), collect_nested_item(line, &mut line_iter))
+                            .map_err(|err| format!("could not parse {} key: {err}", key.trim()))?,
+                    )
+                }
+                "b" => {
+                    b = Some(
+                        Self::from_hcy(Some(value), collect_nested_item(line, &mut line_iter))
+                            .map_err(|err| format!("could not parse b key: {err}"))?,
+                    )
+                }
+                "k" => k = Some(parse_into(value)?),
+                "period" => period = Some(parse_into(value)?),
+                "count" => count = Some(parse_into(value)?),
+                "axes" => {
+                    for axis in value.split(',') {
+                        match axis.trim() {
+                            "x" => axes[0] = true,
+                            "y" => axes[1] = true,
+                            "z" => axes[2] = true,
+                            axis => Err(format!("unknown axis {axis}"))?,
+                        }
+                    }
+                }
+                "extent" => h = Some(parse_into(value)?),
+                "radius" => radius = Some(parse_into(value)?),
+                "thickness" => thickness = Some(parse_into(value)?),
+                "amplitude" => amplitude = Some(parse_into(value)?),
+                "frequency" => frequency = parse_into(value)?,
+                "octaves" => octaves = parse_into(value)?,
+                "color" => color = Some(parse_into(value)?),
+                "translate" => translate = parse_into(value)?,
+                "rotate" => rotate = parse_into(value)?,
+                "scale" => scale = parse_into(value)?,
+                _ => {}
+            }
+        }
+
+        let binary = matches!(
+            member,
+            "union"
+                | "difference"
+                | "intersection"
+                | "smooth union"
+                | "smooth difference"
+                | "smooth intersection"
+        );
+        let a = a.ok_or(if binary {
+            "missing required key `a`"
+        } else {
+            "missing required key `shape`"
+        })?;
+        let b = || b.ok_or("missing required key `b`");
+        let k = || k.ok_or("missing required key `k`");
+
+        Ok(match member {
+            "union" => Box::new(Union { a, b: b()? }),
+            "difference" => Box::new(a.difference(b()?)),
+            "intersection" => Box::new(a.intersection(b()?)),
+            "smooth union" => Box::new(a.smooth_union(k()?, b()?)),
+            "smooth difference" => Box::new(a.smooth_difference(k()?, b()?)),
+            "smooth intersection" => Box::new(a.smooth_intersection(k()?, b()?)),
+            "twist" => Box::new(a.twist(k()?)),
+            "modulo" => Box::new(a.modulo(period.ok_or("missing required key `period`")?)),
+            "repeat" => Box::new(a.repeat(
+                period.ok_or("missing required key `period`")?,
+                count.ok_or("missing required key `count`")?,
+            )),
+            "mirror" => Box::new(a.mirror(axes)),
+            "bend" => Box::new(a.bend(k()?)?),
+            "elongate" => Box::new(a.elongate(h.ok_or("missing required key `extent`")?)),
+            "round" => Box::new(a.round(radius.ok_or("missing required key `radius`")?)),
+            "onion" => Box::new(a.onion(thickness.ok_or("missing required key `thickness`")?)),
+            "displace" => Box::new(a.displace(
+                amplitude.ok_or("missing required key `amplitude`")?,
+                frequency,
+                octaves,
+            )),
+            "paint" => Box::new(a.paint(color.ok_or("missing required key `color`")?)),
+            "transform" => Box::new(a.transformed(
+                mat4::translate(translate) * mat4::rotate_deg(rotate) * mat4::scale(scale),
+            )),
+            _ => Err(format!("unknown sdf type {member}"))?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_nested_trees() {
+        let lines = [
+            "  a: transform",
+            "    translate: 2.0, 0.0, 0.0",
+            "    scale: 2.0, 2.0, 2.0",
+            "    shape: sphere",
+            "      radius: 1.0",
+            "  b: box",
+            "    extent: 0.5, 0.5, 0.5",
+        ]
+        .map(String::from)
+        .to_vec();
+        let sdf = Box::<dyn SDF>::from_hcy(Some("union"), lines).unwrap();
+
+        // the sphere is moved to x = 2 with a radius of 2
+        assert!((sdf.dist(vec3::new(6.0, 0.0, 0.0)) - 2.0).abs() < 0.01);
+        assert!(sdf.dist(vec3::splat(0.0)) < 0.0);
+    }
+
+    #[test]
+    fn rejects_bending_unbounded_shapes() {
+        let lines = ["  a: plane", "    normal: 0.0, 1.0, 0.0", "  k: 0.5"]
+            .map(String::from)
+            .to_vec();
+        let err = Box::<dyn SDF>::from_hcy(Some("bend"), lines).unwrap_err();
+        assert!(err.to_string().contains("bent"));
+        assert!(Plane::new(vec3::unit_y(), 0.0).bend(0.5).is_err());
+    }
+
+    #[test]
+    fn smooth_unions_blend_colours() {
+        let red = Color::new(1.0, 0.0, 0.0);
+        let blue = Color::new(0.0, 0.0, 1.0);
+        let sdf = Sphere::new(1.0, vec3::new(-1.0, 0.0, 0.0))
+            .paint(red)
+            .smooth_union(0.5, Sphere::new(1.0, vec3::new(1.0, 0.0, 0.0)).paint(blue));
+
+        assert_eq!(sdf.color(vec3::new(-2.0, 0.0, 0.0)), Some(red));
+        let middle = sdf.color(vec3::splat(0.0)).unwrap();
+        assert!((middle.r - 0.5).abs() < 0.001 && (middle.b - 0.5).abs() < 0.001);
+    }
+}
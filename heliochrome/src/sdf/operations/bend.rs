@@ -0,0 +1,66 @@
+use std::error::Error;
+
+use crate::{
+    color::Color,
+    hittables::AABB,
+    maths::{vec2, vec3},
+    sdf::SDF,
+};
+
+// bends the primitive around the z axis, more so the further along x it is
+#[derive(Debug)]
+pub struct Bend<A: SDF> {
+    pub k: f32,
+    pub primitive: A,
+    // furthest any part of the primitive is from the z axis
+    radius: f32,
+}
+
+impl<A: SDF> Bend<A> {
+    // the primitive has to be bounded, the distance bound would be zero otherwise
+    pub fn new(primitive: A, k: f32) -> Result<Self, Box<dyn Error>> {
+        let aabb = primitive.make_bounding_box();
+        if !aabb.is_finite() {
+            Err("only bounded shapes can be bent")?;
+        }
+        let radius = vec2::new(
+            aabb.min.x.abs().max(aabb.max.x.abs()),
+            aabb.min.y.abs().max(aabb.max.y.abs()),
+        )
+        .mag();
+        Ok(Self {
+            k,
+            primitive,
+            radius,
+        })
+    }
+
+    fn unbend(&self, p: vec3) -> vec3 {
+        let (s, c) = (self.k * p.x).sin_cos();
+        vec3::new(c * p.x - s * p.y, s * p.x + c * p.y, p.z)
+    }
+}
+
+impl<A: SDF> SDF for Bend<A> {
+    fn dist(&self, p: vec3) -> f32 {
+        let q = self.unbend(p);
+        // the rotation changes along x, which stretches space by up to k times the distance
+        // from the z axis. that's furthest at the corners of the box the tracer marches in,
+        // the half diagonal of its xy square.
+        let reach = self.radius * std::f32::consts::SQRT_2;
+        self.primitive.dist(q) / (1.0 + self.k.abs() * reach)
+    }
+
+    fn make_bounding_box(&self) -> AABB {
+        // points only rotate around z so they stay within the same distance of it
+        let aabb = self.primitive.make_bounding_box();
+        AABB::new(
+            vec3::new(-self.radius, -self.radius, aabb.min.z),
+            vec3::new(self.radius, self.radius, aabb.max.z),
+        )
+    }
+
+    fn color(&self, p: vec3) -> Option<Color> {
+        self.primitive.color(self.unbend(p))
+    }
+}
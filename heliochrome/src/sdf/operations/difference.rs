@@ -1,4 +1,9 @@
-use crate::{hittables::AABB, maths::vec3, sdf::SDF};
+use crate::{
+    color::Color,
+    hittables::AABB,
+    maths::vec3,
+    sdf::{mix_colors, SDF},
+};
 
 #[derive(Debug)]
 pub struct Difference<A: SDF, B: SDF> {
@@ -17,4 +22,14 @@ impl<A: SDF, B: SDF> SDF for Difference<A, B> {
     fn make_bounding_box(&self) -> AABB {
         self.a.make_bounding_box()
     }
+
+    fn color(&self, p: vec3) -> Option<Color> {
+        // the cut out part of the surface is b's
+        let surface_a = self.a.dist(p) > -self.b.dist(p);
+        mix_colors(
+            self.a.color(p),
+            self.b.color(p),
+            if surface_a { 1.0 } else { 0.0 },
+        )
+    }
 }
@@ -0,0 +1,40 @@
+use crate::{
+    color::Color,
+    hittables::AABB,
+    maths::{fbm, vec3, NOISE_LIPSCHITZ, NOISE_MAX},
+    sdf::SDF,
+};
+
+// pushes the surface in and out with fractal noise
+#[derive(Debug)]
+pub struct Displace<A: SDF> {
+    pub amplitude: f32,
+    pub frequency: f32,
+    pub octaves: u32,
+    pub primitive: A,
+}
+
+impl<A: SDF> Displace<A> {
+    // every octave adds the same amount of slope, as it is twice as fast but half as tall
+    fn lipschitz(&self) -> f32 {
+        1.0 + self.amplitude.abs() * self.frequency.abs() * NOISE_LIPSCHITZ * self.octaves as f32
+    }
+}
+
+impl<A: SDF> SDF for Displace<A> {
+    fn dist(&self, p: vec3) -> f32 {
+        let displacement = self.amplitude * fbm(p * self.frequency, self.octaves);
+        (self.primitive.dist(p) + displacement) / self.lipschitz()
+    }
+
+    fn make_bounding_box(&self) -> AABB {
+        // the octaves add up to less than twice the first one
+        let aabb = self.primitive.make_bounding_box();
+        let grow = vec3::splat(2.0 * NOISE_MAX * self.amplitude.abs());
+        AABB::new(aabb.min - grow, aabb.max + grow)
+    }
+
+    fn color(&self, p: vec3) -> Option<Color> {
+        self.primitive.color(p)
+    }
+}
@@ -0,0 +1,31 @@
+use crate::{color::Color, hittables::AABB, maths::vec3, sdf::SDF};
+
+// stretches the primitive by `h` in each direction along every axis, keeping its ends intact
+#[derive(Debug)]
+pub struct Elongate<A: SDF> {
+    pub h: vec3,
+    pub primitive: A,
+}
+
+impl<A: SDF> Elongate<A> {
+    // `p` with the stretched out middle removed
+    fn squash(&self, p: vec3) -> vec3 {
+        (p.abs() - self.h).max(&vec3::splat(0.0)) * p.signum()
+    }
+}
+
+impl<A: SDF> SDF for Elongate<A> {
+    fn dist(&self, p: vec3) -> f32 {
+        let q = p.abs() - self.h;
+        self.primitive.dist(self.squash(p)) + q.max_component().min(0.0)
+    }
+
+    fn make_bounding_box(&self) -> AABB {
+        let aabb = self.primitive.make_bounding_box();
+        AABB::new(aabb.min - self.h, aabb.max + self.h)
+    }
+
+    fn color(&self, p: vec3) -> Option<Color> {
+        self.primitive.color(self.squash(p))
+    }
+}
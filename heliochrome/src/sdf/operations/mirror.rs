@@ -0,0 +1,42 @@
+use crate::{color::Color, hittables::AABB, maths::vec3, sdf::SDF};
+
+// reflects space across the planes through the origin of every axis set in `axes`, so only the
+// positive half of the primitive along them is used
+#[derive(Debug)]
+pub struct Mirror<A: SDF> {
+    pub primitive: A,
+    pub axes: [bool; 3],
+}
+
+impl<A: SDF> Mirror<A> {
+    fn fold(&self, p: vec3) -> vec3 {
+        let mut q = p;
+        for a in 0..3 {
+            if self.axes[a] {
+                q[a] = q[a].abs();
+            }
+        }
+        q
+    }
+}
+
+impl<A: SDF> SDF for Mirror<A> {
+    fn dist(&self, p: vec3) -> f32 {
+        self.primitive.dist(self.fold(p))
+    }
+
+    fn make_bounding_box(&self) -> AABB {
+        let mut aabb = self.primitive.make_bounding_box();
+        for a in 0..3 {
+            if self.axes[a] {
+                aabb.max[a] = aabb.max[a].max(0.0);
+                aabb.min[a] = -aabb.max[a];
+            }
+        }
+        aabb
+    }
+
+    fn color(&self, p: vec3) -> Option<Color> {
+        self.primitive.color(self.fold(p))
+    }
+}
@@ -18,3 +18,61 @@ pub use twist::*;
 
 mod modulo;
 pub use modulo::*;
+
+mod union;
+pub use union::*;
+
+mod transformed;
+pub use transformed::*;
+
+mod mirror;
+pub use mirror::*;
+
+mod bend;
+pub use bend::*;
+
+mod elongate;
+pub use elongate::*;
+
+mod round;
+pub use round::*;
+
+mod onion;
+pub use onion::*;
+
+mod repeat;
+pub use repeat::*;
+
+mod displace;
+pub use displace::*;
+
+mod paint;
+pub use paint::*;
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        maths::vec3,
+        sdf::{Sphere, Torus, SDF},
+    };
+
+    // a distance bound can never change faster than the points it is measured from
+    fn assert_lipschitz(sdf: &dyn SDF) {
+        for _ in 0..5000 {
+            let p = vec3::random() * 6.0 - vec3::splat(3.0);
+            let q = p + (vec3::random() - vec3::splat(0.5)) * 0.1;
+            let slope = (sdf.dist(p) - sdf.dist(q)).abs() / (p - q).mag();
+            assert!(slope <= 1.001, "{sdf:?} has a slope of {slope} at {p:?}");
+        }
+    }
+
+    #[test]
+    fn distorted_distances_stay_conservative() {
+        assert_lipschitz(&Torus::new(1.0, 0.3).scale(vec3::new(0.3, 2.0, 1.0)));
+        assert_lipschitz(&Torus::new(1.0, 0.3).bend(0.8).unwrap());
+        assert_lipschitz(&Sphere::new(1.0, vec3::splat(0.0)).displace(0.2, 3.0, 4));
+        assert_lipschitz(
+            &Sphere::new(0.4, vec3::splat(0.0)).repeat(vec3::splat(1.0), vec3::splat(2.0)),
+        );
+    }
+}
@@ -1,6 +1,6 @@
 //TODO: this has some precision issues..
 
-use crate::{hittables::AABB, maths::vec3, sdf::SDF};
+use crate::{color::Color, hittables::AABB, maths::vec3, sdf::SDF};
 
 #[derive(Debug)]
 pub struct Modulo<A: SDF> {
@@ -8,16 +8,24 @@ pub struct Modulo<A: SDF> {
     pub period: vec3,
 }
 
+impl<A: SDF> Modulo<A> {
+    fn wrap(&self, p: vec3) -> vec3 {
+        (p + 0.5 * self.period)
+            - self.period * ((p + 0.5 * self.period) / self.period).floor()
+            - 0.5 * self.period
+    }
+}
+
 impl<A: SDF> SDF for Modulo<A> {
     fn dist(&self, p: vec3) -> f32 {
-        self.primitive.dist(
-            (p + 0.5 * self.period)
-                - self.period * ((p + 0.5 * self.period) / self.period).floor()
-                - 0.5 * self.period,
-        )
+        self.primitive.dist(self.wrap(p))
     }
 
     fn make_bounding_box(&self) -> AABB {
         AABB::new(vec3::splat(-f32::INFINITY), vec3::splat(f32::INFINITY))
     }
+
+    fn color(&self, p: vec3) -> Option<Color> {
+        self.primitive.color(self.wrap(p))
+    }
 }
@@ -0,0 +1,26 @@
+use crate::{color::Color, hittables::AABB, maths::vec3, sdf::SDF};
+
+// hollows the primitive out into a shell `thickness` thick on each side of its surface
+#[derive(Debug)]
+pub struct Onion<A: SDF> {
+    pub thickness: f32,
+    pub primitive: A,
+}
+
+impl<A: SDF> SDF for Onion<A> {
+    fn dist(&self, p: vec3) -> f32 {
+        self.primitive.dist(p).abs() - self.thickness
+    }
+
+    fn make_bounding_box(&self) -> AABB {
+        let aabb = self.primitive.make_bounding_box();
+        AABB::new(
+            aabb.min - vec3::splat(self.thickness),
+            aabb.max + vec3::splat(self.thickness),
+        )
+    }
+
+    fn color(&self, p: vec3) -> Option<Color> {
+        self.primitive.color(p)
+    }
+}
@@ -0,0 +1,22 @@
+use crate::{color::Color, hittables::AABB, maths::vec3, sdf::SDF};
+
+// gives the whole primitive one colour attribute
+#[derive(Debug)]
+pub struct Paint<A: SDF> {
+    pub color: Color,
+    pub primitive: A,
+}
+
+impl<A: SDF> SDF for Paint<A> {
+    fn dist(&self, p: vec3) -> f32 {
+        self.primitive.dist(p)
+    }
+
+    fn make_bounding_box(&self) -> AABB {
+        self.primitive.make_bounding_box()
+    }
+
+    fn color(&self, _p: vec3) -> Option<Color> {
+        Some(self.color)
+    }
+}
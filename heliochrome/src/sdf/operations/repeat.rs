@@ -0,0 +1,36 @@
+use crate::{color::Color, hittables::AABB, maths::vec3, sdf::SDF};
+
+// like `Modulo` but only `count` copies out from the original in each direction, so it stays
+// bounded. the primitive should fit inside one period.
+#[derive(Debug)]
+pub struct Repeat<A: SDF> {
+    pub period: vec3,
+    pub count: vec3,
+    pub primitive: A,
+}
+
+impl<A: SDF> Repeat<A> {
+    // `p` moved into the nearest copy
+    fn wrap(&self, p: vec3) -> vec3 {
+        let cell = p / self.period;
+        let cell = vec3::new(cell.x.round(), cell.y.round(), cell.z.round());
+        let cell = cell.max(&-self.count).min(&self.count);
+        p - self.period * cell
+    }
+}
+
+impl<A: SDF> SDF for Repeat<A> {
+    fn dist(&self, p: vec3) -> f32 {
+        self.primitive.dist(self.wrap(p))
+    }
+
+    fn make_bounding_box(&self) -> AABB {
+        let aabb = self.primitive.make_bounding_box();
+        let offset = (self.period * self.count).abs();
+        AABB::new(aabb.min - offset, aabb.max + offset)
+    }
+
+    fn color(&self, p: vec3) -> Option<Color> {
+        self.primitive.color(self.wrap(p))
+    }
+}
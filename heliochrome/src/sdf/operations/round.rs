@@ -0,0 +1,25 @@
+use crate::{color::Color, hittables::AABB, maths::vec3, sdf::SDF};
+
+#[derive(Debug)]
+pub struct Round<A: SDF> {
+    pub radius: f32,
+    pub primitive: A,
+}
+
+impl<A: SDF> SDF for Round<A> {
+    fn dist(&self, p: vec3) -> f32 {
+        self.primitive.dist(p) - self.radius
+    }
+
+    fn make_bounding_box(&self) -> AABB {
+        let aabb = self.primitive.make_bounding_box();
+        AABB::new(
+            aabb.min - vec3::splat(self.radius),
+            aabb.max + vec3::splat(self.radius),
+        )
+    }
+
+    fn color(&self, p: vec3) -> Option<Color> {
+        self.primitive.color(p)
+    }
+}
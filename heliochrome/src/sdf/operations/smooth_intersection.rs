@@ -1,7 +1,8 @@
 use crate::{
+    color::Color,
     hittables::AABB,
     maths::{lerp, vec3},
-    sdf::SDF,
+    sdf::{mix_colors, SDF},
 };
 
 #[derive(Debug)]
@@ -22,4 +23,11 @@ impl<A: SDF, B: SDF> SDF for SmoothIntersection<A, B> {
     fn make_bounding_box(&self) -> AABB {
         self.a.make_bounding_box()
     }
+
+    fn color(&self, p: vec3) -> Option<Color> {
+        let d1 = self.a.dist(p);
+        let d2 = self.b.dist(p);
+        let h = (0.5 - 0.5 * (d2 - d1) / self.k).clamp(0.0, 1.0);
+        mix_colors(self.a.color(p), self.b.color(p), h)
+    }
 }
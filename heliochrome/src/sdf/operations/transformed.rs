@@ -0,0 +1,68 @@
+use crate::{
+    color::Color,
+    hittables::AABB,
+    maths::{mat4, vec3, vec4},
+    sdf::SDF,
+    transform::Transform,
+};
+
+#[derive(Debug)]
+pub struct Transformed<A: SDF> {
+    pub primitive: A,
+    transform: Transform,
+    // how much the inverse can stretch distances, the local distance is divided by it so the
+    // result never overshoots even with non uniform scales
+    lipschitz: f32,
+}
+
+impl<A: SDF> Transformed<A> {
+    pub fn new(primitive: A, matrix: mat4) -> Self {
+        let transform = Transform::new(matrix);
+        let lipschitz = spectral_norm(&transform.inverse);
+        Self {
+            primitive,
+            transform,
+            lipschitz,
+        }
+    }
+}
+
+// the largest singular value of the linear part of `m`, by power iteration on m^T m
+fn spectral_norm(m: &mat4) -> f32 {
+    let apply = |v: vec3| (*m * vec4::from_vec3(v, 0.0)).to_vec3();
+    let apply_t = |v: vec3| (m.trans_mul(vec4::from_vec3(v, 0.0))).to_vec3();
+
+    let mut v = vec3::new(0.577, 0.577, 0.578);
+    let mut norm = 0.0;
+    for _ in 0..32 {
+        let w = apply_t(apply(v));
+        let mag = w.mag();
+        if mag == 0.0 {
+            return 0.0;
+        }
+        norm = mag.sqrt();
+        v = w / mag;
+    }
+
+    // power iteration approaches the norm from below, pad it a little to stay conservative
+    norm * 1.001
+}
+
+impl<A: SDF> SDF for Transformed<A> {
+    fn dist(&self, p: vec3) -> f32 {
+        self.primitive.dist(self.transform.trans_pos(&p)) / self.lipschitz
+    }
+
+    fn make_bounding_box(&self) -> AABB {
+        let aabb = self.primitive.make_bounding_box();
+        if !aabb.is_finite() {
+            return AABB::new(vec3::splat(-f32::INFINITY), vec3::splat(f32::INFINITY));
+        }
+
+        self.transform.trans_aabb(&aabb)
+    }
+
+    fn color(&self, p: vec3) -> Option<Color> {
+        self.primitive.color(self.transform.trans_pos(&p))
+    }
+}
@@ -1,6 +1,7 @@
 //NOTE: this has precision issues :<
 
 use crate::{
+    color::Color,
     hittables::AABB,
     maths::{mat2, vec2, vec3},
     sdf::SDF,
@@ -12,16 +13,25 @@ pub struct Twist<P: SDF> {
     pub primitive: P,
 }
 
-impl<P: SDF> SDF for Twist<P> {
-    fn dist(&self, p: vec3) -> f32 {
+impl<P: SDF> Twist<P> {
+    fn untwist(&self, p: vec3) -> vec3 {
         let m = mat2::rotate(self.k * p.y);
         let mpxz = m * vec2::new(p.x, p.z);
-        let q = vec3::new(mpxz.x, mpxz.y, p.y);
-        self.primitive.dist(q)
+        vec3::new(mpxz.x, mpxz.y, p.y)
+    }
+}
+
+impl<P: SDF> SDF for Twist<P> {
+    fn dist(&self, p: vec3) -> f32 {
+        self.primitive.dist(self.untwist(p))
     }
 
     fn make_bounding_box(&self) -> AABB {
         //FIXME: this is def not quite right
         AABB::new(vec3::splat(-f32::INFINITY), vec3::splat(f32::INFINITY))
     }
+
+    fn color(&self, p: vec3) -> Option<Color> {
+        self.primitive.color(self.untwist(p))
+    }
 }
@@ -0,0 +1,31 @@
+use crate::{
+    color::Color,
+    hittables::AABB,
+    maths::vec3,
+    sdf::{mix_colors, SDF},
+};
+
+#[derive(Debug)]
+pub struct Union<A: SDF, B: SDF> {
+    pub a: A,
+    pub b: B,
+}
+
+impl<A: SDF, B: SDF> SDF for Union<A, B> {
+    fn dist(&self, p: vec3) -> f32 {
+        self.a.dist(p).min(self.b.dist(p))
+    }
+
+    fn make_bounding_box(&self) -> AABB {
+        AABB::surrounding(&self.a.make_bounding_box(), &self.b.make_bounding_box())
+    }
+
+    fn color(&self, p: vec3) -> Option<Color> {
+        let nearer_a = self.a.dist(p) < self.b.dist(p);
+        mix_colors(
+            self.a.color(p),
+            self.b.color(p),
+            if nearer_a { 1.0 } else { 0.0 },
+        )
+    }
+}
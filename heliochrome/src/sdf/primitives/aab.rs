@@ -1,4 +1,11 @@
-use crate::{hittables::AABB, maths::vec3, sdf::SDF};
+use std::error::Error;
+
+use crate::{
+    hittables::AABB,
+    loader::{parse_into, FromHCY},
+    maths::vec3,
+    sdf::SDF,
+};
 
 #[derive(Debug)]
 pub struct AAB {
@@ -21,3 +28,20 @@ impl SDF for AAB {
         AABB::new(-self.extent, self.extent)
     }
 }
+
+impl FromHCY for AAB {
+    fn from_hcy(_member: Option<&str>, lines: Vec<String>) -> Result<Self, Box<dyn Error>> {
+        let mut extent = None;
+
+        for line in lines.into_iter() {
+            let (key, value) = line
+                .split_once(':')
+                .ok_or("invalid key value pair syntax")?;
+            if key.trim() == "extent" {
+                extent = Some(parse_into(value)?);
+            }
+        }
+
+        Ok(AAB::new(extent.ok_or("missing required key `extent`")?))
+    }
+}
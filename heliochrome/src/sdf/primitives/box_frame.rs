@@ -0,0 +1,60 @@
+use std::error::Error;
+
+use crate::{
+    hittables::AABB,
+    loader::{parse_into, FromHCY},
+    maths::vec3,
+    sdf::SDF,
+};
+
+// only the twelve edges of a box, each `thickness` thick
+#[derive(Debug)]
+pub struct BoxFrame {
+    pub extent: vec3,
+    pub thickness: f32,
+}
+
+impl BoxFrame {
+    pub fn new(extent: vec3, thickness: f32) -> Self {
+        Self { extent, thickness }
+    }
+}
+
+impl SDF for BoxFrame {
+    fn dist(&self, p: vec3) -> f32 {
+        let p = p.abs() - self.extent;
+        let q = (p + vec3::splat(self.thickness)).abs() - vec3::splat(self.thickness);
+        let edge = |v: vec3| v.max(&vec3::splat(0.0)).mag() + v.max_component().min(0.0);
+
+        edge(vec3::new(p.x, q.y, q.z))
+            .min(edge(vec3::new(q.x, p.y, q.z)))
+            .min(edge(vec3::new(q.x, q.y, p.z)))
+    }
+
+    fn make_bounding_box(&self) -> AABB {
+        AABB::new(-self.extent, self.extent)
+    }
+}
+
+impl FromHCY for BoxFrame {
+    fn from_hcy(_member: Option<&str>, lines: Vec<String>) -> Result<Self, Box<dyn Error>> {
+        let mut extent = None;
+        let mut thickness = None;
+
+        for line in lines.into_iter() {
+            let (key, value) = line
+                .split_once(':')
+                .ok_or("invalid key value pair syntax")?;
+            match key.trim() {
+                "extent" => extent = Some(parse_into(value)?),
+                "thickness" => thickness = Some(parse_into(value)?),
+                _ => {}
+            }
+        }
+
+        Ok(BoxFrame::new(
+            extent.ok_or("missing required key `extent`")?,
+            thickness.ok_or("missing required key `thickness`")?,
+        ))
+    }
+}
@@ -0,0 +1,63 @@
+use std::error::Error;
+
+use crate::{
+    hittables::AABB,
+    loader::{parse_into, FromHCY},
+    maths::vec3,
+    sdf::SDF,
+};
+
+#[derive(Debug)]
+pub struct Capsule {
+    pub a: vec3,
+    pub b: vec3,
+    pub radius: f32,
+}
+
+impl Capsule {
+    pub fn new(a: vec3, b: vec3, radius: f32) -> Self {
+        Self { a, b, radius }
+    }
+}
+
+impl SDF for Capsule {
+    fn dist(&self, p: vec3) -> f32 {
+        let pa = p - self.a;
+        let ba = self.b - self.a;
+        let h = (pa.dot(ba) / ba.dot(ba)).clamp(0.0, 1.0);
+        (pa - ba * h).mag() - self.radius
+    }
+
+    fn make_bounding_box(&self) -> AABB {
+        AABB::new(
+            self.a.min(&self.b) - vec3::splat(self.radius),
+            self.a.max(&self.b) + vec3::splat(self.radius),
+        )
+    }
+}
+
+impl FromHCY for Capsule {
+    fn from_hcy(_member: Option<&str>, lines: Vec<String>) -> Result<Self, Box<dyn Error>> {
+        let mut a = None;
+        let mut b = None;
+        let mut radius = None;
+
+        for line in lines.into_iter() {
+            let (key, value) = line
+                .split_once(':')
+                .ok_or("invalid key value pair syntax")?;
+            match key.trim() {
+                "a" => a = Some(parse_into(value)?),
+                "b" => b = Some(parse_into(value)?),
+                "radius" => radius = Some(parse_into(value)?),
+                _ => {}
+            }
+        }
+
+        Ok(Capsule::new(
+            a.ok_or("missing required key `a`")?,
+            b.ok_or("missing required key `b`")?,
+            radius.ok_or("missing required key `radius`")?,
+        ))
+    }
+}
@@ -0,0 +1,76 @@
+use std::error::Error;
+
+use crate::{
+    hittables::AABB,
+    loader::{parse_into, FromHCY},
+    maths::{vec2, vec3},
+    sdf::SDF,
+};
+
+// a capped cone on the y axis going from `bottom_radius` at -height to `top_radius` at height,
+// a top radius of zero gives a pointy cone
+#[derive(Debug)]
+pub struct Cone {
+    pub height: f32,
+    pub bottom_radius: f32,
+    pub top_radius: f32,
+}
+
+impl Cone {
+    pub fn new(height: f32, bottom_radius: f32, top_radius: f32) -> Self {
+        Self {
+            height,
+            bottom_radius,
+            top_radius,
+        }
+    }
+}
+
+impl SDF for Cone {
+    fn dist(&self, p: vec3) -> f32 {
+        let (h, r1, r2) = (self.height, self.bottom_radius, self.top_radius);
+        let q = vec2::new((p.x * p.x + p.z * p.z).sqrt(), p.y);
+        let k1 = vec2::new(r2, h);
+        let k2 = vec2::new(r2 - r1, 2.0 * h);
+        let ca = vec2::new(
+            q.x - q.x.min(if q.y < 0.0 { r1 } else { r2 }),
+            q.y.abs() - h,
+        );
+        let cb = q - k1 + k2 * ((k1 - q).dot(k2) / k2.mag_sq()).clamp(0.0, 1.0);
+        let s = if cb.x < 0.0 && ca.y < 0.0 { -1.0 } else { 1.0 };
+
+        s * ca.mag_sq().min(cb.mag_sq()).sqrt()
+    }
+
+    fn make_bounding_box(&self) -> AABB {
+        let r = self.bottom_radius.max(self.top_radius);
+        let extent = vec3::new(r, self.height, r);
+        AABB::new(-extent, extent)
+    }
+}
+
+impl FromHCY for Cone {
+    fn from_hcy(_member: Option<&str>, lines: Vec<String>) -> Result<Self, Box<dyn Error>> {
+        let mut height = None;
+        let mut bottom_radius = None;
+        let mut top_radius = 0.0;
+
+        for line in lines.into_iter() {
+            let (key, value) = line
+                .split_once(':')
+                .ok_or("invalid key value pair syntax")?;
+            match key.trim() {
+                "height" => height = Some(parse_into(value)?),
+                "bottom radius" => bottom_radius = Some(parse_into(value)?),
+                "top radius" => top_radius = parse_into(value)?,
+                _ => {}
+            }
+        }
+
+        Ok(Cone::new(
+            height.ok_or("missing required key `height`")?,
+            bottom_radius.ok_or("missing required key `bottom radius`")?,
+            top_radius,
+        ))
+    }
+}
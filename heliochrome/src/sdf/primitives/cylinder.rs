@@ -0,0 +1,57 @@
+use std::error::Error;
+
+use crate::{
+    hittables::AABB,
+    loader::{parse_into, FromHCY},
+    maths::{vec2, vec3},
+    sdf::SDF,
+};
+
+// a capped cylinder standing on the y axis, `height` is half of its full height
+#[derive(Debug)]
+pub struct Cylinder {
+    pub radius: f32,
+    pub height: f32,
+}
+
+impl Cylinder {
+    pub fn new(radius: f32, height: f32) -> Self {
+        Self { radius, height }
+    }
+}
+
+impl SDF for Cylinder {
+    fn dist(&self, p: vec3) -> f32 {
+        let d = vec2::new((p.x * p.x + p.z * p.z).sqrt(), p.y).abs()
+            - vec2::new(self.radius, self.height);
+        d.x.max(d.y).min(0.0) + d.max(&vec2::splat(0.0)).mag()
+    }
+
+    fn make_bounding_box(&self) -> AABB {
+        let extent = vec3::new(self.radius, self.height, self.radius);
+        AABB::new(-extent, extent)
+    }
+}
+
+impl FromHCY for Cylinder {
+    fn from_hcy(_member: Option<&str>, lines: Vec<String>) -> Result<Self, Box<dyn Error>> {
+        let mut radius = None;
+        let mut height = None;
+
+        for line in lines.into_iter() {
+            let (key, value) = line
+                .split_once(':')
+                .ok_or("invalid key value pair syntax")?;
+            match key.trim() {
+                "radius" => radius = Some(parse_into(value)?),
+                "height" => height = Some(parse_into(value)?),
+                _ => {}
+            }
+        }
+
+        Ok(Cylinder::new(
+            radius.ok_or("missing required key `radius`")?,
+            height.ok_or("missing required key `height`")?,
+        ))
+    }
+}
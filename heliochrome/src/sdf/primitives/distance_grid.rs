@@ -0,0 +1,229 @@
+use std::error::Error;
+
+use crate::{
+    hittables::{Hittable, Mesh, AABB},
+    loader::{parse_into, FromHCY},
+    maths::{vec3, Ray},
+    sdf::SDF,
+};
+
+// cells of padding around the mesh, so the field outside it is sampled too
+const PADDING: usize = 2;
+
+// signed distances sampled on a regular grid and interpolated trilinearly between samples,
+// see DistanceGrid::from_mesh for turning a closed mesh into one
+#[derive(Debug)]
+pub struct DistanceGrid {
+    // position of the first sample and the spacing between them
+    origin: vec3,
+    cell: f32,
+    dims: [usize; 3],
+    distances: Vec<f32>,
+    bounds: AABB,
+}
+
+impl DistanceGrid {
+    pub fn new(origin: vec3, cell: f32, dims: [usize; 3], distances: Vec<f32>) -> Self {
+        assert_eq!(distances.len(), dims[0] * dims[1] * dims[2]);
+        let far = origin + vec3::new(dims[0] as f32, dims[1] as f32, dims[2] as f32) * cell
+            - vec3::splat(cell);
+        Self {
+            origin,
+            cell,
+            dims,
+            distances,
+            bounds: AABB::new(origin, far),
+        }
+    }
+
+    // samples a closed mesh, with `resolution` cells along its longest side. distances come
+    // from the closest triangle, found exactly near the surface and propagated from the
+    // neighbours everywhere else (Bridson's makelevelset3), signs from counting crossings
+    // along rows of samples.
+    pub fn from_mesh(mesh: &Mesh, resolution: usize) -> Self {
+        let mesh_bounds = mesh.make_bounding_box();
+        let cell = (mesh_bounds.max - mesh_bounds.min).max_component() / resolution.max(1) as f32;
+        let origin = mesh_bounds.min - vec3::splat(PADDING as f32 * cell);
+        let size = (mesh_bounds.max - mesh_bounds.min) / cell;
+        let dims = [0, 1, 2].map(|a| size[a].ceil() as usize + 2 * PADDING + 1);
+
+        let index = |i: usize, j: usize, k: usize| i + dims[0] * (j + dims[1] * k);
+        let position =
+            |i: usize, j: usize, k: usize| origin + vec3::new(i as f32, j as f32, k as f32) * cell;
+        let mut distances = vec![f32::INFINITY; dims[0] * dims[1] * dims[2]];
+        let mut closest = vec![usize::MAX; distances.len()];
+
+        let tris = mesh.triangles();
+        for (t, tri) in tris.iter().enumerate() {
+            let aabb = tri.make_bounding_box();
+            let lo = ((aabb.min - origin) / cell).floor();
+            let hi = ((aabb.max - origin) / cell).floor();
+            let range =
+                |a: usize| (lo[a].max(1.0) as usize - 1)..=(hi[a] as usize + 2).min(dims[a] - 1);
+            for k in range(2) {
+                for j in range(1) {
+                    for i in range(0) {
+                        let p = position(i, j, k);
+                        let d = (tri.closest_point(&p) - p).mag();
+                        let idx = index(i, j, k);
+                        if d < distances[idx] {
+                            distances[idx] = d;
+                            closest[idx] = t;
+                        }
+                    }
+                }
+            }
+        }
+
+        // sweep in all eight diagonal directions, a sample takes the closest triangle of any
+        // already visited neighbour if it's closer than its own
+        for _ in 0..2 {
+            for sweep in 0..8 {
+                let dir = [0, 1, 2].map(|a| if sweep & (1 << a) == 0 { 1 } else { -1 });
+                let order = |a: usize, n: usize| {
+                    if dir[a] > 0 {
+                        n
+                    } else {
+                        dims[a] - 1 - n
+                    }
+                };
+                for k in (1..dims[2]).map(|n| order(2, n)) {
+                    for j in (1..dims[1]).map(|n| order(1, n)) {
+                        for i in (1..dims[0]).map(|n| order(0, n)) {
+                            let p = position(i, j, k);
+                            let idx = index(i, j, k);
+                            for neighbour in 1..8 {
+                                let [ni, nj, nk] = [(i, 0), (j, 1), (k, 2)].map(|(n, a)| {
+                                    if neighbour & (1 << a) == 0 {
+                                        n
+                                    } else {
+                                        (n as isize - dir[a]) as usize
+                                    }
+                                });
+                                let t = closest[index(ni, nj, nk)];
+                                if t == usize::MAX || t == closest[idx] {
+                                    continue;
+                                }
+                                let d = (tris[t].closest_point(&p) - p).mag();
+                                if d < distances[idx] {
+                                    distances[idx] = d;
+                                    closest[idx] = t;
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        // a sample is inside if a ray along x crosses the surface an odd number of times
+        // before reaching it
+        for k in 0..dims[2] {
+            for j in 0..dims[1] {
+                let ray = Ray::new(position(0, j, k), vec3::unit_x());
+                let mut crossings = vec![];
+                let mut t_min = 0.0;
+                while let Some(intersection) = mesh.intersect(&ray, t_min, f32::INFINITY) {
+                    // rays through a shared edge hit both triangles, which is one crossing
+                    if crossings
+                        .last()
+                        .map_or(true, |last: &f32| intersection.t - last > cell * 1e-4)
+                    {
+                        crossings.push(intersection.t);
+                    }
+                    t_min = intersection.t + cell * 1e-5;
+                }
+
+                let mut crossed = 0;
+                for i in 0..dims[0] {
+                    while crossed < crossings.len() && crossings[crossed] < i as f32 * cell {
+                        crossed += 1;
+                    }
+                    if crossed % 2 == 1 {
+                        distances[index(i, j, k)] *= -1.0;
+                    }
+                }
+            }
+        }
+
+        // the interpolated surface can stray from the mesh by a fraction of a cell
+        let mut grid = Self::new(origin, cell, dims, distances);
+        grid.bounds = AABB::new(
+            mesh_bounds.min - vec3::splat(cell),
+            mesh_bounds.max + vec3::splat(cell),
+        );
+        grid
+    }
+
+    fn sample(&self, i: usize, j: usize, k: usize) -> f32 {
+        self.distances[i + self.dims[0] * (j + self.dims[1] * k)]
+    }
+}
+
+impl SDF for DistanceGrid {
+    fn dist(&self, p: vec3) -> f32 {
+        // points outside the grid are measured from the closest point on its edge, the
+        // surface is at least `d` from there in some direction, so at least this far away
+        let q = (p - self.origin) / self.cell;
+        let mut outside = 0.0;
+        let mut base = [0; 3];
+        let mut frac = vec3::default();
+        for a in 0..3 {
+            let max = (self.dims[a] - 1) as f32;
+            let clamped = q[a].clamp(0.0, max);
+            outside += (q[a] - clamped).powi(2);
+            base[a] = (clamped.floor() as usize).min(self.dims[a].saturating_sub(2));
+            frac[a] = clamped - base[a] as f32;
+        }
+
+        let [i, j, k] = base;
+        let lerp = |a: f32, b: f32, t: f32| a + (b - a) * t;
+        let x00 = lerp(self.sample(i, j, k), self.sample(i + 1, j, k), frac.x);
+        let x10 = lerp(
+            self.sample(i, j + 1, k),
+            self.sample(i + 1, j + 1, k),
+            frac.x,
+        );
+        let x01 = lerp(
+            self.sample(i, j, k + 1),
+            self.sample(i + 1, j, k + 1),
+            frac.x,
+        );
+        let x11 = lerp(
+            self.sample(i, j + 1, k + 1),
+            self.sample(i + 1, j + 1, k + 1),
+            frac.x,
+        );
+        let d = lerp(lerp(x00, x10, frac.y), lerp(x01, x11, frac.y), frac.z);
+
+        if outside > 0.0 {
+            let o = outside.sqrt() * self.cell;
+            (d.max(0.0).powi(2) + o * o).sqrt()
+        } else {
+            d
+        }
+    }
+
+    fn make_bounding_box(&self) -> AABB {
+        self.bounds
+    }
+}
+
+impl FromHCY for DistanceGrid {
+    // the keys of a mesh hittable, with the number of cells along the longest side
+    fn from_hcy(_member: Option<&str>, lines: Vec<String>) -> Result<Self, Box<dyn Error>> {
+        let mut resolution = 64;
+
+        for line in lines.iter() {
+            let (key, value) = line
+                .split_once(':')
+                .ok_or("invalid key value pair syntax")?;
+            if key.trim() == "resolution" {
+                resolution = parse_into(value)?;
+            }
+        }
+
+        let mesh = Mesh::from_hcy(None, lines)?;
+        Ok(DistanceGrid::from_mesh(&mesh, resolution))
+    }
+}
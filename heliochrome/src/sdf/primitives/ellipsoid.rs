@@ -0,0 +1,49 @@
+use std::error::Error;
+
+use crate::{
+    hittables::AABB,
+    loader::{parse_into, FromHCY},
+    maths::vec3,
+    sdf::SDF,
+};
+
+// NOTE: this is only a bound, not the exact distance, so it marches a bit slower
+#[derive(Debug)]
+pub struct Ellipsoid {
+    pub radii: vec3,
+}
+
+impl Ellipsoid {
+    pub fn new(radii: vec3) -> Self {
+        Self { radii }
+    }
+}
+
+impl SDF for Ellipsoid {
+    fn dist(&self, p: vec3) -> f32 {
+        let k0 = (p / self.radii).mag();
+        let k1 = (p / (self.radii * self.radii)).mag();
+        k0 * (k0 - 1.0) / k1
+    }
+
+    fn make_bounding_box(&self) -> AABB {
+        AABB::new(-self.radii, self.radii)
+    }
+}
+
+impl FromHCY for Ellipsoid {
+    fn from_hcy(_member: Option<&str>, lines: Vec<String>) -> Result<Self, Box<dyn Error>> {
+        let mut radii = None;
+
+        for line in lines.into_iter() {
+            let (key, value) = line
+                .split_once(':')
+                .ok_or("invalid key value pair syntax")?;
+            if key.trim() == "radii" {
+                radii = Some(parse_into(value)?);
+            }
+        }
+
+        Ok(Ellipsoid::new(radii.ok_or("missing required key `radii`")?))
+    }
+}
@@ -0,0 +1,70 @@
+use std::error::Error;
+
+use crate::{
+    hittables::AABB,
+    loader::{parse_into, FromHCY},
+    maths::{vec2, vec3},
+    sdf::SDF,
+};
+
+// a hexagonal prism along the z axis, `radius` is to the middle of the flat sides and
+// `height` is half its length
+#[derive(Debug)]
+pub struct HexPrism {
+    pub radius: f32,
+    pub height: f32,
+}
+
+impl HexPrism {
+    pub fn new(radius: f32, height: f32) -> Self {
+        Self { radius, height }
+    }
+}
+
+impl SDF for HexPrism {
+    fn dist(&self, p: vec3) -> f32 {
+        const K: vec3 = vec3::new(-0.866_025_4, 0.5, 0.577_350_3);
+        let k_xy = vec2::new(K.x, K.y);
+
+        let p = p.abs();
+        let xy = vec2::new(p.x, p.y);
+        let xy = xy - 2.0 * k_xy.dot(xy).min(0.0) * k_xy;
+        let edge = K.z * self.radius;
+        let d = vec2::new(
+            (xy - vec2::new(xy.x.clamp(-edge, edge), self.radius)).mag()
+                * (xy.y - self.radius).signum(),
+            p.z - self.height,
+        );
+
+        d.x.max(d.y).min(0.0) + d.max(&vec2::splat(0.0)).mag()
+    }
+
+    fn make_bounding_box(&self) -> AABB {
+        // the corners are on the x axis
+        let extent = vec3::new(self.radius / 0.866_025_4, self.radius, self.height);
+        AABB::new(-extent, extent)
+    }
+}
+
+impl FromHCY for HexPrism {
+    fn from_hcy(_member: Option<&str>, lines: Vec<String>) -> Result<Self, Box<dyn Error>> {
+        let mut radius = None;
+        let mut height = None;
+
+        for line in lines.into_iter() {
+            let (key, value) = line
+                .split_once(':')
+                .ok_or("invalid key value pair syntax")?;
+            match key.trim() {
+                "radius" => radius = Some(parse_into(value)?),
+                "height" => height = Some(parse_into(value)?),
+                _ => {}
+            }
+        }
+
+        Ok(HexPrism::new(
+            radius.ok_or("missing required key `radius`")?,
+            height.ok_or("missing required key `height`")?,
+        ))
+    }
+}
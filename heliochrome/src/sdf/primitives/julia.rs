@@ -0,0 +1,97 @@
+use std::error::Error;
+
+use crate::{
+    color::Color,
+    hittables::AABB,
+    loader::{parse_into, FromHCY},
+    maths::{vec3, vec4},
+    sdf::{OrbitTrap, SDF},
+};
+
+// the 3d slice w = 0 of the quaternion julia set of z^2 + c
+#[derive(Debug)]
+pub struct Julia {
+    pub c: vec4,
+    pub iterations: u32,
+    pub orbit_trap: Option<OrbitTrap>,
+}
+
+impl Julia {
+    pub fn new(c: vec4, iterations: u32) -> Self {
+        Self {
+            c,
+            iterations,
+            orbit_trap: None,
+        }
+    }
+
+    // the distance estimate and the closest the orbit came to the origin
+    fn iterate(&self, p: vec3) -> (f32, f32) {
+        const BAILOUT: f32 = 256.0;
+
+        let mut z = vec4::new(p.x, p.y, p.z, 0.0);
+        let mut mz2 = z.mag_sq();
+        // squared magnitude of the derivative
+        let mut md2 = 1.0;
+        let mut trap = mz2;
+        for _ in 0..self.iterations {
+            md2 *= 4.0 * mz2;
+            z = vec4::new(
+                z.x * z.x - z.y * z.y - z.z * z.z - z.w * z.w,
+                2.0 * z.x * z.y,
+                2.0 * z.x * z.z,
+                2.0 * z.x * z.w,
+            ) + self.c;
+            mz2 = z.mag_sq();
+            trap = trap.min(mz2);
+            if mz2 > BAILOUT {
+                break;
+            }
+        }
+
+        (0.25 * (mz2 / md2).sqrt() * mz2.ln(), trap.sqrt())
+    }
+}
+
+impl SDF for Julia {
+    fn dist(&self, p: vec3) -> f32 {
+        self.iterate(p).0
+    }
+
+    fn make_bounding_box(&self) -> AABB {
+        // nothing past this radius stays bounded
+        let r = self.c.mag().max(2.0);
+        AABB::new(vec3::splat(-r), vec3::splat(r))
+    }
+
+    fn color(&self, p: vec3) -> Option<Color> {
+        let orbit_trap = self.orbit_trap.as_ref()?;
+        Some(orbit_trap.color(self.iterate(p).1))
+    }
+}
+
+impl FromHCY for Julia {
+    fn from_hcy(_member: Option<&str>, lines: Vec<String>) -> Result<Self, Box<dyn Error>> {
+        let mut c = None;
+        let mut iterations = 11;
+        let mut inner = None;
+        let mut outer = None;
+
+        for line in lines.into_iter() {
+            let (key, value) = line
+                .split_once(':')
+                .ok_or("invalid key value pair syntax")?;
+            match key.trim() {
+                "c" => c = Some(parse_into(value)?),
+                "iterations" => iterations = parse_into(value)?,
+                "inner color" => inner = Some(parse_into(value)?),
+                "outer color" => outer = Some(parse_into(value)?),
+                _ => {}
+            }
+        }
+
+        let mut julia = Julia::new(c.ok_or("missing required key `c`")?, iterations);
+        julia.orbit_trap = OrbitTrap::from_keys(inner, outer)?;
+        Ok(julia)
+    }
+}
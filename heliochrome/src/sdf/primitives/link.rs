@@ -0,0 +1,68 @@
+use std::error::Error;
+
+use crate::{
+    hittables::AABB,
+    loader::{parse_into, FromHCY},
+    maths::{vec2, vec3},
+    sdf::SDF,
+};
+
+// a chain link in the xy plane, a torus pulled apart by `length` along y
+#[derive(Debug)]
+pub struct Link {
+    pub length: f32,
+    pub r_major: f32,
+    pub r_minor: f32,
+}
+
+impl Link {
+    pub fn new(length: f32, r_major: f32, r_minor: f32) -> Self {
+        Self {
+            length,
+            r_major,
+            r_minor,
+        }
+    }
+}
+
+impl SDF for Link {
+    fn dist(&self, p: vec3) -> f32 {
+        let q = vec3::new(p.x, (p.y.abs() - self.length).max(0.0), p.z);
+        vec2::new(vec2::new(q.x, q.y).mag() - self.r_major, q.z).mag() - self.r_minor
+    }
+
+    fn make_bounding_box(&self) -> AABB {
+        let extent = vec3::new(
+            self.r_major + self.r_minor,
+            self.length + self.r_major + self.r_minor,
+            self.r_minor,
+        );
+        AABB::new(-extent, extent)
+    }
+}
+
+impl FromHCY for Link {
+    fn from_hcy(_member: Option<&str>, lines: Vec<String>) -> Result<Self, Box<dyn Error>> {
+        let mut length = None;
+        let mut r_major = None;
+        let mut r_minor = None;
+
+        for line in lines.into_iter() {
+            let (key, value) = line
+                .split_once(':')
+                .ok_or("invalid key value pair syntax")?;
+            match key.trim() {
+                "length" => length = Some(parse_into(value)?),
+                "major radius" => r_major = Some(parse_into(value)?),
+                "minor radius" => r_minor = Some(parse_into(value)?),
+                _ => {}
+            }
+        }
+
+        Ok(Link::new(
+            length.ok_or("missing required key `length`")?,
+            r_major.ok_or("missing required key `major radius`")?,
+            r_minor.ok_or("missing required key `minor radius`")?,
+        ))
+    }
+}
@@ -1,29 +1,42 @@
-use crate::{hittables::AABB, maths::vec3, sdf::SDF};
+use std::error::Error;
+
+use crate::{
+    color::Color,
+    hittables::AABB,
+    loader::{parse_into, FromHCY},
+    maths::vec3,
+    sdf::{OrbitTrap, SDF},
+};
 
 #[derive(Debug)]
 pub struct MandelBulb {
     p: f32,
+    pub orbit_trap: Option<OrbitTrap>,
 }
 
 impl MandelBulb {
     pub fn new(p: f32) -> Self {
-        Self { p }
+        Self {
+            p,
+            orbit_trap: None,
+        }
     }
-}
 
-impl SDF for MandelBulb {
-    fn dist(&self, p: vec3) -> f32 {
+    // the distance estimate and the closest the orbit came to the origin
+    fn iterate(&self, p: vec3) -> (f32, f32) {
         const ITERATIONS: usize = 4;
         const BAILOUT: f32 = 256.0;
 
         let mut z = p;
         let mut dr = 1.0;
         let mut r = 0.0;
+        let mut trap = f32::INFINITY;
         for _ in 0..=ITERATIONS {
             r = z.mag();
             if r > BAILOUT {
                 break;
             }
+            trap = trap.min(r);
 
             let theta = (z.z / r).acos() * self.p;
             let stheta = theta.sin();
@@ -39,10 +52,45 @@ impl SDF for MandelBulb {
             z += p;
         }
 
-        0.5 * r.ln() * r / dr
+        (0.5 * r.ln() * r / dr, trap)
+    }
+}
+
+impl SDF for MandelBulb {
+    fn dist(&self, p: vec3) -> f32 {
+        self.iterate(p).0
     }
 
     fn make_bounding_box(&self) -> AABB {
         AABB::new(vec3::splat(-2.0), vec3::splat(2.0))
     }
+
+    fn color(&self, p: vec3) -> Option<Color> {
+        let orbit_trap = self.orbit_trap.as_ref()?;
+        Some(orbit_trap.color(self.iterate(p).1))
+    }
+}
+
+impl FromHCY for MandelBulb {
+    fn from_hcy(_member: Option<&str>, lines: Vec<String>) -> Result<Self, Box<dyn Error>> {
+        let mut power = 8.0;
+        let mut inner = None;
+        let mut outer = None;
+
+        for line in lines.into_iter() {
+            let (key, value) = line
+                .split_once(':')
+                .ok_or("invalid key value pair syntax")?;
+            match key.trim() {
+                "power" => power = parse_into(value)?,
+                "inner color" => inner = Some(parse_into(value)?),
+                "outer color" => outer = Some(parse_into(value)?),
+                _ => {}
+            }
+        }
+
+        let mut bulb = MandelBulb::new(power);
+        bulb.orbit_trap = OrbitTrap::from_keys(inner, outer)?;
+        Ok(bulb)
+    }
 }
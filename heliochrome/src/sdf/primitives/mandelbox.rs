@@ -0,0 +1,106 @@
+use std::error::Error;
+
+use crate::{
+    color::Color,
+    hittables::AABB,
+    loader::{parse_into, FromHCY},
+    maths::vec3,
+    sdf::{OrbitTrap, SDF},
+};
+
+const MIN_RADIUS_SQ: f32 = 0.25;
+const FIXED_RADIUS_SQ: f32 = 1.0;
+
+#[derive(Debug)]
+pub struct Mandelbox {
+    pub scale: f32,
+    pub iterations: u32,
+    pub orbit_trap: Option<OrbitTrap>,
+}
+
+impl Mandelbox {
+    pub fn new(scale: f32, iterations: u32) -> Self {
+        Self {
+            scale,
+            iterations,
+            orbit_trap: None,
+        }
+    }
+
+    // the distance estimate and the closest the orbit came to the origin
+    fn iterate(&self, p: vec3) -> (f32, f32) {
+        let mut z = p;
+        let mut dr = 1.0;
+        let mut trap = p.mag_sq();
+        for _ in 0..self.iterations {
+            // box fold
+            z = z.clamp(-1.0, 1.0) * 2.0 - z;
+
+            // sphere fold
+            let r2 = z.mag_sq();
+            let fold = if r2 < MIN_RADIUS_SQ {
+                FIXED_RADIUS_SQ / MIN_RADIUS_SQ
+            } else if r2 < FIXED_RADIUS_SQ {
+                FIXED_RADIUS_SQ / r2
+            } else {
+                1.0
+            };
+            z *= fold;
+            dr *= fold;
+
+            z = z * self.scale + p;
+            dr = dr * self.scale.abs() + 1.0;
+            trap = trap.min(z.mag_sq());
+        }
+
+        (z.mag() / dr.abs(), trap.sqrt())
+    }
+}
+
+impl SDF for Mandelbox {
+    fn dist(&self, p: vec3) -> f32 {
+        self.iterate(p).0
+    }
+
+    fn make_bounding_box(&self) -> AABB {
+        // everything further out escapes, see Lowe's original description
+        let s = self.scale.abs();
+        let r = if s > 1.0 {
+            2.0 * (s + 1.0) / (s - 1.0)
+        } else {
+            f32::INFINITY
+        };
+        AABB::new(vec3::splat(-r), vec3::splat(r))
+    }
+
+    fn color(&self, p: vec3) -> Option<Color> {
+        let orbit_trap = self.orbit_trap.as_ref()?;
+        Some(orbit_trap.color(self.iterate(p).1))
+    }
+}
+
+impl FromHCY for Mandelbox {
+    fn from_hcy(_member: Option<&str>, lines: Vec<String>) -> Result<Self, Box<dyn Error>> {
+        let mut scale = 2.0;
+        let mut iterations = 12;
+        let mut inner = None;
+        let mut outer = None;
+
+        for line in lines.into_iter() {
+            let (key, value) = line
+                .split_once(':')
+                .ok_or("invalid key value pair syntax")?;
+            match key.trim() {
+                "scale" => scale = parse_into(value)?,
+                "iterations" => iterations = parse_into(value)?,
+                "inner color" => inner = Some(parse_into(value)?),
+                "outer color" => outer = Some(parse_into(value)?),
+                _ => {}
+            }
+        }
+
+        let mut mandelbox = Mandelbox::new(scale, iterations);
+        mandelbox.orbit_trap = OrbitTrap::from_keys(inner, outer)?;
+        Ok(mandelbox)
+    }
+}
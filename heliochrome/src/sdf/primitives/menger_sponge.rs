@@ -0,0 +1,63 @@
+use std::error::Error;
+
+use crate::{
+    hittables::AABB,
+    loader::{parse_into, FromHCY},
+    maths::vec3,
+    sdf::SDF,
+};
+
+// the sponge fills the cube from -1 to 1
+#[derive(Debug)]
+pub struct MengerSponge {
+    pub iterations: u32,
+}
+
+impl MengerSponge {
+    pub fn new(iterations: u32) -> Self {
+        Self { iterations }
+    }
+}
+
+impl SDF for MengerSponge {
+    fn dist(&self, p: vec3) -> f32 {
+        let q = p.abs() - vec3::splat(1.0);
+        let mut d = q.max(&vec3::splat(0.0)).mag() + q.max_component().min(0.0);
+
+        let mut s = 1.0;
+        for _ in 0..self.iterations {
+            // glsl's mod, which stays positive for negative inputs
+            let a = p * s;
+            let a = a - 2.0 * (a / 2.0).floor() - vec3::splat(1.0);
+            s *= 3.0;
+            let r = (vec3::splat(1.0) - 3.0 * a.abs()).abs();
+            let da = r.x.max(r.y);
+            let db = r.y.max(r.z);
+            let dc = r.z.max(r.x);
+            d = d.max((da.min(db).min(dc) - 1.0) / s);
+        }
+
+        d
+    }
+
+    fn make_bounding_box(&self) -> AABB {
+        AABB::new(vec3::splat(-1.0), vec3::splat(1.0))
+    }
+}
+
+impl FromHCY for MengerSponge {
+    fn from_hcy(_member: Option<&str>, lines: Vec<String>) -> Result<Self, Box<dyn Error>> {
+        let mut iterations = 4;
+
+        for line in lines.into_iter() {
+            let (key, value) = line
+                .split_once(':')
+                .ok_or("invalid key value pair syntax")?;
+            if key.trim() == "iterations" {
+                iterations = parse_into(value)?;
+            }
+        }
+
+        Ok(MengerSponge::new(iterations))
+    }
+}
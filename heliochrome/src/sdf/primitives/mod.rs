@@ -9,3 +9,86 @@ pub use aab::*;
 
 mod mandel_bulb;
 pub use mandel_bulb::*;
+
+mod rounded_box;
+pub use rounded_box::*;
+
+mod box_frame;
+pub use box_frame::*;
+
+mod capsule;
+pub use capsule::*;
+
+mod cylinder;
+pub use cylinder::*;
+
+mod cone;
+pub use cone::*;
+
+mod plane;
+pub use plane::*;
+
+mod hex_prism;
+pub use hex_prism::*;
+
+mod ellipsoid;
+pub use ellipsoid::*;
+
+mod octahedron;
+pub use octahedron::*;
+
+mod link;
+pub use link::*;
+
+mod menger_sponge;
+pub use menger_sponge::*;
+
+mod julia;
+pub use julia::*;
+
+mod mandelbox;
+pub use mandelbox::*;
+
+mod distance_grid;
+pub use distance_grid::*;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{maths::vec3, maths::vec4, sdf::SDF};
+
+    // nothing outside of a bounding box may be inside the shape
+    #[test]
+    fn bounding_boxes_contain_the_shape() {
+        let shapes: Vec<Box<dyn SDF>> = vec![
+            Box::new(RoundedBox::new(vec3::new(1.0, 2.0, 0.5), 0.2)),
+            Box::new(BoxFrame::new(vec3::new(1.0, 2.0, 0.5), 0.1)),
+            Box::new(Capsule::new(
+                vec3::splat(-1.0),
+                vec3::new(1.0, 2.0, 0.0),
+                0.5,
+            )),
+            Box::new(Cylinder::new(0.5, 1.5)),
+            Box::new(Cone::new(1.0, 1.0, 0.3)),
+            Box::new(HexPrism::new(0.8, 0.5)),
+            Box::new(Ellipsoid::new(vec3::new(1.0, 0.5, 2.0))),
+            Box::new(Octahedron::new(1.2)),
+            Box::new(Link::new(0.5, 0.6, 0.2)),
+            Box::new(MengerSponge::new(3)),
+            Box::new(Julia::new(vec4::new(-0.2, 0.6, 0.2, 0.0), 11)),
+            Box::new(Mandelbox::new(2.0, 12)),
+        ];
+
+        for shape in shapes {
+            let aabb = shape.make_bounding_box();
+            for _ in 0..2000 {
+                let p =
+                    aabb.min + (aabb.max - aabb.min) * (vec3::random() * 1.5 - vec3::splat(0.25));
+                let outside = (0..3).any(|a| p[a] < aabb.min[a] || p[a] > aabb.max[a]);
+                if outside {
+                    assert!(shape.dist(p) > -0.001, "{shape:?} is inside at {p:?}");
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use std::error::Error;
+
+use crate::{
+    hittables::AABB,
+    loader::{parse_into, FromHCY},
+    maths::vec3,
+    sdf::SDF,
+};
+
+// a regular octahedron with its corners `size` along each axis
+#[derive(Debug)]
+pub struct Octahedron {
+    pub size: f32,
+}
+
+impl Octahedron {
+    pub fn new(size: f32) -> Self {
+        Self { size }
+    }
+}
+
+impl SDF for Octahedron {
+    fn dist(&self, p: vec3) -> f32 {
+        let s = self.size;
+        let p = p.abs();
+        let m = p.x + p.y + p.z - s;
+        let q = if 3.0 * p.x < m {
+            p
+        } else if 3.0 * p.y < m {
+            vec3::new(p.y, p.z, p.x)
+        } else if 3.0 * p.z < m {
+            vec3::new(p.z, p.x, p.y)
+        } else {
+            return m * 0.577_350_27;
+        };
+
+        let k = (0.5 * (q.z - q.y + s)).clamp(0.0, s);
+        vec3::new(q.x, q.y - s + k, q.z - k).mag()
+    }
+
+    fn make_bounding_box(&self) -> AABB {
+        AABB::new(vec3::splat(-self.size), vec3::splat(self.size))
+    }
+}
+
+impl FromHCY for Octahedron {
+    fn from_hcy(_member: Option<&str>, lines: Vec<String>) -> Result<Self, Box<dyn Error>> {
+        let mut size = None;
+
+        for line in lines.into_iter() {
+            let (key, value) = line
+                .split_once(':')
+                .ok_or("invalid key value pair syntax")?;
+            if key.trim() == "size" {
+                size = Some(parse_into(value)?);
+            }
+        }
+
+        Ok(Octahedron::new(size.ok_or("missing required key `size`")?))
+    }
+}
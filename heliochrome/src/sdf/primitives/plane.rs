@@ -0,0 +1,67 @@
+use std::error::Error;
+
+use crate::{
+    hittables::AABB,
+    loader::{parse_into, FromHCY},
+    maths::vec3,
+    sdf::SDF,
+};
+
+// everything below the plane through `normal * offset` is inside
+#[derive(Debug)]
+pub struct Plane {
+    normal: vec3,
+    offset: f32,
+}
+
+impl Plane {
+    pub fn new(normal: vec3, offset: f32) -> Self {
+        Self {
+            normal: normal.normalized(),
+            offset,
+        }
+    }
+}
+
+impl SDF for Plane {
+    fn dist(&self, p: vec3) -> f32 {
+        p.dot(self.normal) - self.offset
+    }
+
+    fn make_bounding_box(&self) -> AABB {
+        // a half space is only bounded when its normal is along an axis
+        let mut min = vec3::splat(-f32::INFINITY);
+        let mut max = vec3::splat(f32::INFINITY);
+        for a in 0..3 {
+            if self.normal[a].abs() == 1.0 {
+                if self.normal[a] > 0.0 {
+                    max[a] = self.offset;
+                } else {
+                    min[a] = -self.offset;
+                }
+            }
+        }
+
+        AABB::new(min, max)
+    }
+}
+
+impl FromHCY for Plane {
+    fn from_hcy(_member: Option<&str>, lines: Vec<String>) -> Result<Self, Box<dyn Error>> {
+        let mut normal = vec3::unit_y();
+        let mut offset = 0.0;
+
+        for line in lines.into_iter() {
+            let (key, value) = line
+                .split_once(':')
+                .ok_or("invalid key value pair syntax")?;
+            match key.trim() {
+                "normal" => normal = parse_into(value)?,
+                "offset" => offset = parse_into(value)?,
+                _ => {}
+            }
+        }
+
+        Ok(Plane::new(normal, offset))
+    }
+}
@@ -0,0 +1,55 @@
+use std::error::Error;
+
+use crate::{
+    hittables::AABB,
+    loader::{parse_into, FromHCY},
+    maths::vec3,
+    sdf::SDF,
+};
+
+// a box of half size `extent` with its edges rounded off by `radius`
+#[derive(Debug)]
+pub struct RoundedBox {
+    pub extent: vec3,
+    pub radius: f32,
+}
+
+impl RoundedBox {
+    pub fn new(extent: vec3, radius: f32) -> Self {
+        Self { extent, radius }
+    }
+}
+
+impl SDF for RoundedBox {
+    fn dist(&self, p: vec3) -> f32 {
+        let q = p.abs() - self.extent + vec3::splat(self.radius);
+        q.max(&vec3::splat(0.0)).mag() + q.max_component().min(0.0) - self.radius
+    }
+
+    fn make_bounding_box(&self) -> AABB {
+        AABB::new(-self.extent, self.extent)
+    }
+}
+
+impl FromHCY for RoundedBox {
+    fn from_hcy(_member: Option<&str>, lines: Vec<String>) -> Result<Self, Box<dyn Error>> {
+        let mut extent = None;
+        let mut radius = None;
+
+        for line in lines.into_iter() {
+            let (key, value) = line
+                .split_once(':')
+                .ok_or("invalid key value pair syntax")?;
+            match key.trim() {
+                "extent" => extent = Some(parse_into(value)?),
+                "radius" => radius = Some(parse_into(value)?),
+                _ => {}
+            }
+        }
+
+        Ok(RoundedBox::new(
+            extent.ok_or("missing required key `extent`")?,
+            radius.ok_or("missing required key `radius`")?,
+        ))
+    }
+}
@@ -1,4 +1,11 @@
-use crate::{hittables::AABB, maths::vec3, sdf::SDF};
+use std::error::Error;
+
+use crate::{
+    hittables::AABB,
+    loader::{parse_into, FromHCY},
+    maths::vec3,
+    sdf::SDF,
+};
 
 #[derive(Debug)]
 pub struct Sphere {
@@ -24,3 +31,26 @@ impl SDF for Sphere {
         )
     }
 }
+
+impl FromHCY for Sphere {
+    fn from_hcy(_member: Option<&str>, lines: Vec<String>) -> Result<Self, Box<dyn Error>> {
+        let mut radius = None;
+        let mut center = vec3::splat(0.0);
+
+        for line in lines.into_iter() {
+            let (key, value) = line
+                .split_once(':')
+                .ok_or("invalid key value pair syntax")?;
+            match key.trim() {
+                "radius" => radius = Some(parse_into(value)?),
+                "center" => center = parse_into(value)?,
+                _ => {}
+            }
+        }
+
+        Ok(Sphere::new(
+            radius.ok_or("missing required key `radius`")?,
+            center,
+        ))
+    }
+}
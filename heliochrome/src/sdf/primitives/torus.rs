@@ -1,5 +1,8 @@
+use std::error::Error;
+
 use crate::{
     hittables::AABB,
+    loader::{parse_into, FromHCY},
     maths::{vec2, vec3},
     sdf::SDF,
 };
@@ -31,3 +34,26 @@ impl SDF for Torus {
         AABB::new(-extent, extent)
     }
 }
+
+impl FromHCY for Torus {
+    fn from_hcy(_member: Option<&str>, lines: Vec<String>) -> Result<Self, Box<dyn Error>> {
+        let mut r_major = None;
+        let mut r_minor = None;
+
+        for line in lines.into_iter() {
+            let (key, value) = line
+                .split_once(':')
+                .ok_or("invalid key value pair syntax")?;
+            match key.trim() {
+                "major radius" => r_major = Some(parse_into(value)?),
+                "minor radius" => r_minor = Some(parse_into(value)?),
+                _ => {}
+            }
+        }
+
+        Ok(Torus::new(
+            r_major.ok_or("missing required key `major radius`")?,
+            r_minor.ok_or("missing required key `minor radius`")?,
+        ))
+    }
+}
@@ -0,0 +1,253 @@
+use std::error::Error;
+use std::f32::consts::{FRAC_PI_2, PI};
+
+use crate::{
+    color::Color,
+    loader::{parse_into, FromHCY},
+    maths::vec3,
+    pdf::ConePdf,
+};
+
+// preetham's sky luminance is in kcd/m^2, this brings it closer to the scale of the other
+// lights in a scene
+const SKY_SCALE: f32 = 0.1;
+// luminance of the sun before the atmosphere in kcd/m^2
+const SUN_LUMINANCE: f32 = 1.6e6;
+// wavelengths in micrometers used for the r, g and b channels of the sun
+const SUN_WAVELENGTHS: [f32; 3] = [0.65, 0.55, 0.45];
+
+// coefficients of the perez sky distribution function
+#[derive(Clone, Copy, Debug)]
+struct Perez {
+    a: f32,
+    b: f32,
+    c: f32,
+    d: f32,
+    e: f32,
+}
+
+impl Perez {
+    // theta is the angle of the view direction from the zenith and gamma its angle to the sun
+    fn eval(&self, theta: f32, gamma: f32) -> f32 {
+        (1.0 + self.a * (self.b / theta.cos().max(0.01)).exp())
+            * (1.0 + self.c * (self.d * gamma).exp() + self.e * gamma.cos() * gamma.cos())
+    }
+}
+
+// "A Practical Analytic Model for Daylight" (Preetham, Shirley and Smits)
+#[derive(Clone, Debug)]
+pub struct PhysicalSky {
+    // unit vector towards the sun
+    pub sun_direction: vec3,
+    pub turbidity: f32,
+    pub ground_albedo: Color,
+    pub intensity: f32,
+
+    perez: [Perez; 3],
+    // luminance and chromaticity at the zenith, divided by the perez function at the zenith
+    zenith: [f32; 3],
+    sun_radiance: Color,
+    cos_sun: f32,
+    ground: Color,
+}
+
+impl PhysicalSky {
+    pub fn new(
+        sun_direction: vec3,
+        turbidity: f32,
+        ground_albedo: Color,
+        sun_diameter: f32,
+    ) -> Self {
+        let sun_direction = sun_direction.normalized();
+        let t = turbidity;
+        // the model is only valid for the sun above the horizon
+        let theta_s = sun_direction
+            .y
+            .clamp(-1.0, 1.0)
+            .acos()
+            .min(FRAC_PI_2 - 0.01);
+
+        let perez = [
+            Perez {
+                a: 0.1787 * t - 1.4630,
+                b: -0.3554 * t + 0.4275,
+                c: -0.0227 * t + 5.3251,
+                d: 0.1206 * t - 2.5771,
+                e: -0.0670 * t + 0.3703,
+            },
+            Perez {
+                a: -0.0193 * t - 0.2592,
+                b: -0.0665 * t + 0.0008,
+                c: -0.0004 * t + 0.2125,
+                d: -0.0641 * t - 0.8989,
+                e: -0.0033 * t + 0.0452,
+            },
+            Perez {
+                a: -0.0167 * t - 0.2608,
+                b: -0.0950 * t + 0.0092,
+                c: -0.0079 * t + 0.2102,
+                d: -0.0441 * t - 1.6537,
+                e: -0.0109 * t + 0.0529,
+            },
+        ];
+
+        let chi = (4.0 / 9.0 - t / 120.0) * (PI - 2.0 * theta_s);
+        let zenith_y = (4.0453 * t - 4.9710) * chi.tan() - 0.2155 * t + 2.4192;
+        let theta = [theta_s.powi(3), theta_s.powi(2), theta_s, 1.0];
+        let poly = |m: [[f32; 4]; 3]| {
+            (0..3)
+                .map(|i| t.powi(2 - i as i32) * (0..4).map(|j| m[i][j] * theta[j]).sum::<f32>())
+                .sum::<f32>()
+        };
+        let zenith_x = poly([
+            [0.00166, -0.00375, 0.00209, 0.0],
+            [-0.02903, 0.06377, -0.03202, 0.00394],
+            [0.11693, -0.21196, 0.06052, 0.25886],
+        ]);
+        let zenith_yc = poly([
+            [0.00275, -0.00610, 0.00317, 0.0],
+            [-0.04214, 0.08970, -0.04153, 0.00516],
+            [0.15346, -0.26756, 0.06670, 0.26688],
+        ]);
+
+        let zenith = [
+            zenith_y.max(0.0) / perez[0].eval(0.0, theta_s),
+            zenith_x / perez[1].eval(0.0, theta_s),
+            zenith_yc / perez[2].eval(0.0, theta_s),
+        ];
+
+        let mut sky = Self {
+            sun_direction,
+            turbidity,
+            ground_albedo,
+            intensity: 1.0,
+            perez,
+            zenith,
+            sun_radiance: Self::sun_radiance(sun_direction.y, turbidity),
+            cos_sun: (sun_diameter * 0.5).to_radians().cos(),
+            ground: Color::splat(0.0),
+        };
+
+        // the ground is lit by the sun and roughly the sky straight above it
+        let sun_irradiance =
+            sky.sun_radiance * 2.0 * PI * (1.0 - sky.cos_sun) * sun_direction.y.max(0.0);
+        let sky_irradiance = sky.sky_radiance(&vec3::unit_y()) * PI;
+        sky.ground = ground_albedo * (sun_irradiance + sky_irradiance) / PI;
+        sky
+    }
+
+    // the sun dimmed by rayleigh and aerosol scattering along its path through the atmosphere
+    fn sun_radiance(cos_theta: f32, turbidity: f32) -> Color {
+        if cos_theta <= 0.0 {
+            return Color::splat(0.0);
+        }
+
+        let theta = cos_theta.acos().to_degrees();
+        let air_mass = 1.0 / (cos_theta + 0.15 * (93.885 - theta).powf(-1.253));
+        let beta = 0.04608365 * turbidity - 0.04586025;
+
+        let mut color = Color::default();
+        for (i, lambda) in SUN_WAVELENGTHS.iter().enumerate() {
+            let rayleigh = (-0.008735 * lambda.powf(-4.08) * air_mass).exp();
+            let aerosol = (-beta * lambda.powf(-1.3) * air_mass).exp();
+            color[i] = SUN_LUMINANCE * SKY_SCALE * rayleigh * aerosol;
+        }
+        color
+    }
+
+    fn sky_radiance(&self, dir: &vec3) -> Color {
+        let theta = dir.y.clamp(0.0, 1.0).acos();
+        let gamma = dir.dot(self.sun_direction).clamp(-1.0, 1.0).acos();
+
+        let y = self.zenith[0] * self.perez[0].eval(theta, gamma) * SKY_SCALE;
+        let x = self.zenith[1] * self.perez[1].eval(theta, gamma);
+        let yc = self.zenith[2] * self.perez[2].eval(theta, gamma);
+
+        // xyY to XYZ to linear srgb
+        let cx = x * y / yc;
+        let cz = (1.0 - x - yc) * y / yc;
+        Color::new(
+            3.2406 * cx - 1.5372 * y - 0.4986 * cz,
+            -0.9689 * cx + 1.8758 * y + 0.0415 * cz,
+            0.0557 * cx - 0.2040 * y + 1.0570 * cz,
+        )
+        .max(&Color::splat(0.0))
+    }
+
+    pub fn sample(&self, dir: &vec3) -> Color {
+        let dir = dir.normalized();
+        if dir.y < 0.0 {
+            return self.ground * self.intensity;
+        }
+
+        let mut color = self.sky_radiance(&dir);
+        if dir.dot(self.sun_direction) >= self.cos_sun {
+            color += self.sun_radiance;
+        }
+        color * self.intensity
+    }
+
+    // the sun is far too small and bright to be found by chance so it gets sampled directly
+    pub fn sun_pdf(&self) -> Option<ConePdf> {
+        if self.sun_direction.y <= 0.0 {
+            None
+        } else {
+            Some(ConePdf::new(self.sun_direction, self.cos_sun))
+        }
+    }
+}
+
+impl FromHCY for PhysicalSky {
+    fn from_hcy(_member: Option<&str>, lines: Vec<String>) -> Result<Self, Box<dyn Error>> {
+        let mut sun_direction = None;
+        let mut turbidity = 3.0;
+        let mut ground_albedo = Color::splat(0.3);
+        let mut sun_size = 0.53;
+        let mut intensity = 1.0;
+
+        for line in lines.into_iter() {
+            let (key, value) = line
+                .split_once(':')
+                .ok_or("invalid key value pair syntax")?;
+            match key.trim() {
+                "sun direction" => sun_direction = Some(parse_into(value)?),
+                "turbidity" => turbidity = parse_into(value)?,
+                "ground albedo" => ground_albedo = parse_into(value)?,
+                "sun size" => sun_size = parse_into(value)?,
+                "intensity" => intensity = parse_into(value)?,
+                _ => {}
+            }
+        }
+
+        if sun_size <= 0.0 {
+            Err("sun size has to be larger than zero")?;
+        }
+
+        let mut sky = PhysicalSky::new(
+            sun_direction.ok_or("missing required key `sun direction`")?,
+            turbidity,
+            ground_albedo,
+            sun_size,
+        );
+        sky.intensity = intensity;
+        Ok(sky)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::pdf::ProbabilityDensityFn;
+
+    #[test]
+    fn suns_need_a_size() {
+        let lines = ["sun direction: 0.0, 1.0, 0.0", "sun size: 0.0"]
+            .map(String::from)
+            .to_vec();
+        assert!(PhysicalSky::from_hcy(None, lines).is_err());
+
+        // a cone too narrow for f32 still has a finite density
+        let pdf = ConePdf::new(vec3::unit_y(), 1.0);
+        assert!(pdf.value(&vec3::unit_y()).is_finite());
+    }
+}
@@ -0,0 +1,130 @@
+use std::{error::Error, path::Path, sync::Arc};
+
+use crate::{
+    color::Color,
+    hittables::BounceInfo,
+    image::Image,
+    loader::{parse_into, FromHCY},
+    maths::{vec2, vec3},
+};
+
+#[derive(Clone, Debug)]
+pub enum Texture {
+    Solid(Color),
+    Image(Arc<Image>),
+    Checker { a: Color, b: Color, scale: f32 },
+    // the color the surface was hit with, for shapes that carry one
+    Attribute { fallback: Color },
+}
+
+impl Texture {
+    pub fn sample(&self, uv: &vec2, p: &vec3) -> Color {
+        match self {
+            Texture::Solid(c) => *c,
+            Texture::Image(img) => {
+                // wrap so tiled uvs (planes, boxes) repeat the image
+                img.sample_uv(&vec2::new(uv.x.rem_euclid(1.0), uv.y.rem_euclid(1.0)))
+            }
+            Texture::Checker { a, b, scale } => {
+                let q = (*p * *scale).floor();
+                if (q.x + q.y + q.z) as i32 % 2 == 0 {
+                    *a
+                } else {
+                    *b
+                }
+            }
+            Texture::Attribute { fallback } => *fallback,
+        }
+    }
+
+    pub fn sample_hit(&self, hit: &BounceInfo) -> Color {
+        match self {
+            Texture::Attribute { fallback } => hit.color.unwrap_or(*fallback),
+            _ => self.sample(&hit.uv, &hit.p),
+        }
+    }
+}
+
+impl From<Color> for Texture {
+    fn from(c: Color) -> Self {
+        Texture::Solid(c)
+    }
+}
+
+impl FromHCY for Texture {
+    fn from_hcy(member: Option<&str>, lines: Vec<String>) -> Result<Self, Box<dyn Error>> {
+        let member = member.ok_or("invalid syntax missing member specifier")?;
+        match member.trim() {
+            "image" => {
+                let mut path = None;
+                let mut alpha = false;
+
+                for line in lines.into_iter() {
+                    let (key, value) = line
+                        .split_once(':')
+                        .ok_or("invalid key value pair syntax")?;
+                    match key.trim() {
+                        "path" => path = Some(value.trim().to_owned()),
+                        "alpha" => alpha = parse_into(value)?,
+                        _ => {}
+                    }
+                }
+
+                let path = path.ok_or("missing required key `path`")?;
+                let img = if alpha {
+                    Image::load_alpha(Path::new(&path))?
+                } else {
+                    Image::load_texture(Path::new(&path))?
+                };
+                Ok(Texture::Image(Arc::new(img)))
+            }
+            "checker" => {
+                let mut a = None;
+                let mut b = None;
+                let mut scale = 1.0;
+
+                for line in lines.into_iter() {
+                    let (key, value) = line
+                        .split_once(':')
+                        .ok_or("invalid key value pair syntax")?;
+                    match key.trim() {
+                        "a" => a = Some(parse_into(value)?),
+                        "b" => b = Some(parse_into(value)?),
+                        "scale" => scale = parse_into(value)?,
+                        _ => {}
+                    }
+                }
+
+                Ok(Texture::Checker {
+                    a: a.ok_or("missing required key `a`")?,
+                    b: b.ok_or("missing required key `b`")?,
+                    scale,
+                })
+            }
+            "attribute" => {
+                let mut fallback = Color::splat(0.5);
+
+                for line in lines.into_iter() {
+                    let (key, value) = line
+                        .split_once(':')
+                        .ok_or("invalid key value pair syntax")?;
+                    if key.trim() == "fallback" {
+                        fallback = parse_into(value)?;
+                    }
+                }
+
+                Ok(Texture::Attribute { fallback })
+            }
+            // anything else is a constant, either a full color or a single value
+            value => {
+                if let Ok(c) = parse_into::<Color>(value) {
+                    Ok(Texture::Solid(c))
+                } else {
+                    Ok(Texture::Solid(Color::splat(
+                        parse_into(value).map_err(|_| format!("unknown texture {value}"))?,
+                    )))
+                }
+            }
+        }
+    }
+}
@@ -1,16 +1,70 @@
-use std::error::Error;
+use std::{borrow::Cow, error::Error};
 
 use crate::{
     hittables::AABB,
-    loader::{parse_into, FromHCY},
-    maths::{mat4, vec3, vec4, Ray},
+    loader::{collect_nested_item, parse_into, FromHCY},
+    maths::{mat4, quat, vec3, vec4, Ray},
 };
 
+// how many poses between two keyframes are used to bound the motion, rotations can swing an
+// object outside of the boxes at the keyframes themselves
+const MOTION_BOUND_STEPS: u32 = 16;
+
+// a decomposed transform at a point in time, so it can be interpolated without shearing
+#[derive(Debug, Clone, Copy)]
+pub struct Keyframe {
+    pub time: f32,
+    pub translate: vec3,
+    pub rotation: quat,
+    pub scale: vec3,
+}
+
+impl Keyframe {
+    pub fn new(time: f32, translate: vec3, rotation: quat, scale: vec3) -> Self {
+        Self {
+            time,
+            translate,
+            rotation,
+            scale,
+        }
+    }
+
+    pub fn matrix(&self) -> mat4 {
+        mat4::translate(self.translate) * self.rotation.to_mat4() * mat4::scale(self.scale)
+    }
+
+    // undoes each part in reverse, which is much cheaper than inverting `matrix`
+    pub fn inverse_matrix(&self) -> mat4 {
+        mat4::scale(self.scale.recip())
+            * self.rotation.conjugate().to_mat4()
+            * mat4::translate(-self.translate)
+    }
+
+    fn transform(&self) -> Transform {
+        Transform {
+            matrix: self.matrix(),
+            inverse: self.inverse_matrix(),
+            keyframes: vec![],
+        }
+    }
+
+    pub fn lerp(&self, other: &Keyframe, t: f32) -> Keyframe {
+        Keyframe::new(
+            self.time * (1.0 - t) + other.time * t,
+            self.translate * (1.0 - t) + other.translate * t,
+            self.rotation.slerp(other.rotation, t),
+            self.scale * (1.0 - t) + other.scale * t,
+        )
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Transform {
     pub matrix: mat4,
     pub inverse: mat4,
     // pub normal_matrix: mat4,
+    // sorted by time, when there are any `matrix` is only the first of them
+    pub keyframes: Vec<Keyframe>,
 }
 
 impl Transform {
@@ -18,13 +72,47 @@ impl Transform {
         Transform {
             matrix,
             inverse: matrix.inverse(),
+            keyframes: vec![],
+        }
+    }
+
+    pub fn new_animated(mut keyframes: Vec<Keyframe>) -> Transform {
+        keyframes.sort_by(|a, b| a.time.total_cmp(&b.time));
+        let mut transform =
+            Transform::new(keyframes.first().map_or(mat4::identity(), |k| k.matrix()));
+        transform.keyframes = keyframes;
+        transform
+    }
+
+    pub fn is_animated(&self) -> bool {
+        self.keyframes.len() > 1
+    }
+
+    fn keyframe_at(&self, time: f32) -> Keyframe {
+        let next = self
+            .keyframes
+            .partition_point(|k| k.time <= time)
+            .clamp(1, self.keyframes.len() - 1);
+        let (a, b) = (&self.keyframes[next - 1], &self.keyframes[next]);
+        let t = ((time - a.time) / (b.time - a.time)).clamp(0.0, 1.0);
+        a.lerp(b, if t.is_nan() { 0.0 } else { t })
+    }
+
+    // the static transform at `time`, which is just this one if it doesn't move. callers
+    // should get it once per ray and reuse it.
+    pub fn at(&self, time: f32) -> Cow<'_, Transform> {
+        if self.is_animated() {
+            Cow::Owned(self.keyframe_at(time).transform())
+        } else {
+            Cow::Borrowed(self)
         }
     }
 
     pub fn trans_ray(&self, r: &Ray) -> Ray {
-        Ray::new(
+        Ray::new_at_time(
             (self.inverse * vec4::from_vec3(r.origin, 1.0)).to_vec3(),
             (self.inverse * vec4::from_vec3(r.direction, 0.0)).to_vec3(),
+            r.time,
         )
     }
 
@@ -49,6 +137,20 @@ impl Transform {
     }
 
     pub fn trans_aabb(&self, aabb: &AABB) -> AABB {
+        if self.is_animated() {
+            // cover every pose the object passes through
+            let start = self.keyframes[0].time;
+            let end = self.keyframes[self.keyframes.len() - 1].time;
+            let steps = MOTION_BOUND_STEPS * (self.keyframes.len() as u32 - 1);
+            return (0..=steps)
+                .map(|i| {
+                    let time = start + (end - start) * i as f32 / steps as f32;
+                    self.keyframe_at(time).transform().trans_aabb(aabb)
+                })
+                .reduce(|a, b| AABB::surrounding(&a, &b))
+                .unwrap();
+        }
+
         let mut min = self.matrix[3].to_vec3();
         let mut max = self.matrix[3].to_vec3();
 
@@ -72,22 +174,93 @@ impl Transform {
 
 impl FromHCY for Transform {
     fn from_hcy(_member: Option<&str>, lines: Vec<String>) -> Result<Self, Box<dyn Error>> {
-        let mut translate = mat4::identity();
-        let mut rotate = mat4::identity();
-        let mut scale = mat4::identity();
+        let mut translate = vec3::splat(0.0);
+        let mut rotate = vec3::splat(0.0);
+        let mut scale = vec3::splat(1.0);
+        let mut keyframes = vec![];
 
-        for line in lines.into_iter() {
+        let mut line_iter = lines.iter();
+        while let Some(line) = line_iter.next() {
             let (key, value) = line
                 .split_once(':')
                 .ok_or("invalid key value pair syntax")?;
             match key.trim() {
-                "translate" => translate = mat4::translate(parse_into(value)?),
-                "rotate" => rotate = mat4::rotate_deg(parse_into(value)?),
-                "scale" => scale = mat4::scale(parse_into(value)?),
+                "translate" => translate = parse_into(value)?,
+                "rotate" => rotate = parse_into(value)?,
+                "scale" => scale = parse_into(value)?,
+                "keyframe" => keyframes.push((
+                    parse_into::<f32>(value)?,
+                    collect_nested_item(line, &mut line_iter),
+                )),
                 _ => {}
             }
         }
 
-        Ok(Transform::new(translate * rotate * scale))
+        if keyframes.is_empty() {
+            return Ok(Transform::new(
+                mat4::translate(translate) * mat4::rotate_deg(rotate) * mat4::scale(scale),
+            ));
+        }
+
+        // anything a keyframe leaves out is taken from the transform block
+        let keyframes = keyframes
+            .into_iter()
+            .map(|(time, lines)| {
+                let mut translate = translate;
+                let mut rotate = rotate;
+                let mut scale = scale;
+                for line in lines.into_iter() {
+                    let (key, value) = line
+                        .split_once(':')
+                        .ok_or("invalid key value pair syntax")?;
+                    match key.trim() {
+                        "translate" => translate = parse_into(value)?,
+                        "rotate" => rotate = parse_into(value)?,
+                        "scale" => scale = parse_into(value)?,
+                        _ => {}
+                    }
+                }
+
+                Ok(Keyframe::new(
+                    time,
+                    translate,
+                    quat::from_rotation_matrix(&mat4::rotate_deg(rotate)),
+                    scale,
+                ))
+            })
+            .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+
+        Ok(Transform::new_animated(keyframes))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn poses_between_keyframes_invert() {
+        let transform = Transform::new_animated(vec![
+            Keyframe::new(
+                0.0,
+                vec3::splat(0.0),
+                quat::from_euler(vec3::splat(0.0)),
+                vec3::splat(1.0),
+            ),
+            Keyframe::new(
+                1.0,
+                vec3::new(1.0, 2.0, 3.0),
+                quat::from_euler(vec3::new(0.5, 1.0, -0.3)),
+                vec3::new(2.0, 0.5, 1.0),
+            ),
+        ]);
+
+        let pose = transform.at(0.3);
+        assert!(matches!(pose, Cow::Owned(_)));
+        let p = vec3::new(0.3, -1.2, 0.7);
+        assert!((pose.trans_pos(&pose.trans_point(&p)) - p).mag() < 1e-5);
+
+        let fixed = Transform::new(mat4::translate(vec3::new(1.0, 0.0, 0.0)));
+        assert!(matches!(fixed.at(0.3), Cow::Borrowed(_)));
     }
 }